@@ -0,0 +1,490 @@
+use crate::branch::Branch;
+use crate::classes::{AcsClasses, ClearanceLevel};
+use crate::instances::AcsInstance;
+use crate::page::Location;
+use crate::sort_json::SortableField;
+use crate::text::clean_text;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, collections::BTreeMap};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SCPInfo {
+	pub actual_number: String,
+	pub display_number: String,
+	// Plain text, with entities decoded
+	pub name: String,
+	// The name as it is formatted on the series page
+	#[serde(default)]
+	pub name_html: String,
+	pub url: String,
+}
+
+impl SCPInfo {
+	// An entry whose name has no formatting and whose number is displayed as it is
+	pub fn new(actual_number: &str, name: &str, url: &str) -> Self {
+		SCPInfo {
+			actual_number: actual_number.to_string(),
+			display_number: actual_number.to_string(),
+			name: name.to_string(),
+			name_html: name.to_string(),
+			url: url.to_string(),
+		}
+	}
+}
+
+impl SortableField for SCPInfo {
+	fn get_field(&self, field: &str) -> Cow<'_, str> {
+		match field {
+			"actual_number" => Cow::Borrowed(&self.actual_number),
+			"display_number" => Cow::Borrowed(&self.display_number),
+			"name" => Cow::Borrowed(&self.name),
+			"name_html" => Cow::Borrowed(&self.name_html),
+			"url" => Cow::Borrowed(&self.url),
+			_ => panic!("Invalid field: {}", field),
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BacklinksInfo {
+	pub fragment: bool,
+	pub name: String,
+	pub actual_number: String,
+	pub url: String,
+	// Slugs of the component followed by the wrapper pages the page includes it through
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub include_path: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SharedAcs {
+	pub contain: String,
+	pub secondary: String,
+	pub disrupt: String,
+	pub scraper: String,
+}
+
+// Finished entries are far larger than the component-level results, which only live
+// until they are wrapped with into_vanilla
+#[allow(clippy::large_enum_variant)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum Acs {
+	Vanilla {
+		#[serde(flatten)]
+		shared: SharedAcs,
+
+		name: String,
+		actual_number: String,
+		display_number: String,
+		clearance: String,
+		clearance_text: String,
+		risk: String,
+		url: String,
+		fragment: bool,
+
+		// Canonical classes normalised from the scraped text above
+		#[serde(default)]
+		classes: AcsClasses,
+
+		// Every ACS instance on pages with more than one, e.g. in tabs or collapsibles
+		#[serde(default, skip_serializing_if = "Vec::is_empty")]
+		instances: Vec<AcsInstance>,
+
+		// Where on the page the Backup extractor found each field
+		#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+		regions: BTreeMap<String, Location>,
+	},
+	Bar {
+		#[serde(flatten)]
+		shared: SharedAcs,
+
+		clearance: String,
+		clearance_text: String,
+		risk: String,
+	},
+	Flops {
+		#[serde(flatten)]
+		shared: SharedAcs,
+
+		clearance: String,
+		clearance_text: String,
+	},
+	Aim {
+		#[serde(flatten)]
+		shared: SharedAcs,
+
+		clearance: String,
+	},
+	Backup {
+		#[serde(flatten)]
+		shared: SharedAcs,
+
+		risk: String,
+
+		#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+		regions: BTreeMap<String, Location>,
+	},
+}
+
+impl SharedAcs {
+	fn get_shared_field(&self, field: &str) -> Option<Cow<'_, str>> {
+		match field {
+			"contain" => Some(Cow::Borrowed(&self.contain)),
+			"secondary" => Some(Cow::Borrowed(&self.secondary)),
+			"disrupt" => Some(Cow::Borrowed(&self.disrupt)),
+			"scraper" => Some(Cow::Borrowed(&self.scraper)),
+			_ => None,
+		}
+	}
+}
+
+// The fields a database of finished entries can be sorted by
+pub const SORT_FIELDS: [&str; 12] = [
+	"actual_number",
+	"display_number",
+	"name",
+	"url",
+	"fragment",
+	"contain",
+	"secondary",
+	"disrupt",
+	"risk",
+	"clearance",
+	"clearance_text",
+	"scraper",
+];
+
+impl SortableField for Acs {
+	fn get_field(&self, field: &str) -> Cow<'_, str> {
+		match self {
+			Acs::Vanilla {
+				shared,
+				name,
+				actual_number,
+				display_number,
+				clearance,
+				clearance_text,
+				risk,
+				url,
+				fragment,
+				..
+			} => {
+				if let Some(shared_field) = shared.get_shared_field(field) {
+					return shared_field;
+				}
+				match field {
+					"name" => Cow::Borrowed(name),
+					"actual_number" => Cow::Borrowed(actual_number),
+					"display_number" => Cow::Borrowed(display_number),
+					"clearance" => Cow::Borrowed(clearance),
+					"clearance_text" => Cow::Borrowed(clearance_text),
+					"risk" => Cow::Borrowed(risk),
+					"url" => Cow::Borrowed(url),
+					"fragment" => Cow::Owned(fragment.to_string()),
+					_ => panic!("Invalid field: {}", field),
+				}
+			}
+			Acs::Bar {
+				shared,
+				clearance,
+				clearance_text,
+				risk,
+				..
+			} => {
+				if let Some(shared_field) = shared.get_shared_field(field) {
+					return shared_field;
+				}
+				match field {
+					"clearance" => Cow::Borrowed(clearance),
+					"clearance_text" => Cow::Borrowed(clearance_text),
+					"risk" => Cow::Borrowed(risk),
+					_ => panic!("Invalid field: {}", field),
+				}
+			}
+			Acs::Flops {
+				shared,
+				clearance,
+				clearance_text,
+				..
+			} => {
+				if let Some(shared_field) = shared.get_shared_field(field) {
+					return shared_field;
+				}
+				match field {
+					"clearance" => Cow::Borrowed(clearance),
+					"clearance_text" => Cow::Borrowed(clearance_text),
+					_ => panic!("Invalid field: {}", field),
+				}
+			}
+			Acs::Aim {
+				shared, clearance, ..
+			} => {
+				if let Some(shared_field) = shared.get_shared_field(field) {
+					return shared_field;
+				}
+				match field {
+					"clearance" => Cow::Borrowed(clearance),
+					_ => panic!("Invalid field: {}", field),
+				}
+			}
+			Acs::Backup { shared, risk, .. } => {
+				if let Some(shared_field) = shared.get_shared_field(field) {
+					return shared_field;
+				}
+				match field {
+					"risk" => Cow::Borrowed(risk),
+					_ => panic!("Invalid field: {}", field),
+				}
+			}
+		}
+	}
+}
+
+impl Acs {
+	// The page a finished entry was scraped from
+	pub fn url(&self) -> Option<&str> {
+		match self {
+			Acs::Vanilla { url, .. } => Some(url),
+			_ => None,
+		}
+	}
+
+	// The canonical classes. Entries written before the classes were recorded are
+	// normalised again from the scraped text, recorded ones are kept since a branch may
+	// have translated them from localised names
+	pub fn normalized_classes(&self) -> AcsClasses {
+		if let Acs::Vanilla { classes, .. } = self {
+			if *classes != AcsClasses::default() {
+				return classes.clone();
+			}
+		}
+
+		let (shared, clearance, risk) = match self {
+			Acs::Vanilla {
+				shared,
+				clearance,
+				risk,
+				..
+			}
+			| Acs::Bar {
+				shared,
+				clearance,
+				risk,
+				..
+			} => (shared, clearance.as_str(), risk.as_str()),
+			Acs::Flops {
+				shared, clearance, ..
+			}
+			| Acs::Aim { shared, clearance } => (shared, clearance.as_str(), ""),
+			Acs::Backup { shared, risk, .. } => (shared, "", risk.as_str()),
+		};
+
+		AcsClasses::normalize(
+			&shared.contain,
+			&shared.secondary,
+			&shared.disrupt,
+			risk,
+			clearance,
+		)
+	}
+
+	// Wraps a component-level result (Bar, Flops, Aim, Backup) with the page's identity
+	pub fn into_vanilla(
+		self,
+		name: &str,
+		actual_number: &str,
+		display_number: &str,
+		url: &str,
+		fragment: &bool,
+	) -> Acs {
+		match self {
+			Acs::Vanilla { .. } => self,
+			Acs::Bar {
+				shared,
+				clearance,
+				clearance_text,
+				risk,
+			} => Acs::Vanilla {
+				shared,
+				name: name.to_string(),
+				actual_number: actual_number.to_string(),
+				display_number: display_number.to_string(),
+				clearance,
+				clearance_text,
+				risk,
+				url: url.to_string(),
+				fragment: *fragment,
+				classes: AcsClasses::default(),
+				instances: Vec::new(),
+				regions: BTreeMap::new(),
+			},
+			Acs::Flops {
+				shared,
+				clearance,
+				clearance_text,
+			} => Acs::Vanilla {
+				shared,
+				name: name.to_string(),
+				actual_number: actual_number.to_string(),
+				display_number: display_number.to_string(),
+				clearance,
+				clearance_text,
+				risk: String::new(),
+				url: url.to_string(),
+				fragment: *fragment,
+				classes: AcsClasses::default(),
+				instances: Vec::new(),
+				regions: BTreeMap::new(),
+			},
+			Acs::Aim { shared, clearance } => Acs::Vanilla {
+				shared,
+				name: name.to_string(),
+				actual_number: actual_number.to_string(),
+				display_number: display_number.to_string(),
+				clearance,
+				clearance_text: String::new(),
+				risk: String::new(),
+				url: url.to_string(),
+				fragment: *fragment,
+				classes: AcsClasses::default(),
+				instances: Vec::new(),
+				regions: BTreeMap::new(),
+			},
+			Acs::Backup {
+				shared,
+				risk,
+				regions,
+			} => Acs::Vanilla {
+				shared,
+				name: name.to_string(),
+				actual_number: actual_number.to_string(),
+				display_number: display_number.to_string(),
+				clearance: String::new(),
+				clearance_text: String::new(),
+				risk,
+				url: url.to_string(),
+				fragment: *fragment,
+				classes: AcsClasses::default(),
+				instances: Vec::new(),
+				regions,
+			},
+		}
+	}
+}
+
+// Normalises a Vanilla entry: cleans every field, fills in defaults and maps the
+// classes to the canonical vocabulary. Classes the entry already has are kept, a branch
+// may have translated them from localised names the English vocabulary doesn't know
+pub fn create_acs(acs: Acs) -> Result<Acs> {
+	match acs {
+		Acs::Vanilla {
+			shared,
+			name,
+			actual_number,
+			display_number,
+			clearance,
+			clearance_text,
+			risk,
+			url,
+			fragment,
+			instances,
+			regions,
+			classes,
+		} => {
+			let mut actual_number = actual_number.clone();
+			if name.to_lowercase().contains("scp-") {
+				actual_number = name.clone();
+			}
+
+			let mut clearance_text = clearance_text.clone();
+
+			if clearance_text.is_empty() {
+				// Level 0 has no text of its own on the ACS
+				clearance_text = ClearanceLevel::normalize(&clearance)
+					.filter(|level| *level != ClearanceLevel::ForOfficialUseOnly)
+					.map(|level| level.text().to_string())
+					.unwrap_or_default();
+			}
+
+			let shared = SharedAcs {
+				contain: clean_text(&shared.contain),
+				secondary: clean_text(&shared.secondary),
+				disrupt: clean_text(&shared.disrupt),
+				scraper: clean_text(&shared.scraper),
+			};
+			let clearance = clean_text(&clearance);
+			let risk = clean_text(&risk);
+			let classes = if classes == AcsClasses::default() {
+				AcsClasses::normalize(
+					&shared.contain,
+					&shared.secondary,
+					&shared.disrupt,
+					&risk,
+					&clearance,
+				)
+			} else {
+				classes
+			};
+
+			Ok(Acs::Vanilla {
+				shared,
+				name,
+				actual_number,
+				display_number,
+				clearance,
+				clearance_text: clean_text(&clearance_text),
+				risk,
+				url,
+				fragment,
+				classes,
+				instances,
+				regions,
+			})
+		}
+		_ => {
+			log::error!("The provided Acs data is not of variant Vanilla: {:?}", acs);
+			Err(anyhow::anyhow!(
+				"The provided Acs data is not of variant Vanilla"
+			))
+		}
+	}
+}
+
+// Turns the instances found on a page into its finished entry: the primary instance
+// with the page's identity, the branch's classes translated and everything normalised.
+// None when no instance was found
+pub fn finish_acs(
+	branch: &Branch,
+	instances: Vec<AcsInstance>,
+	name: &str,
+	actual_number: &str,
+	display_number: &str,
+	url: &str,
+	fragment: &bool,
+) -> Result<Option<Acs>> {
+	let Some(primary) = instances
+		.iter()
+		.find(|instance| instance.primary)
+		.map(|instance| instance.acs.clone())
+	else {
+		return Ok(None);
+	};
+
+	let mut vanilla_acs =
+		primary.into_vanilla(name, actual_number, display_number, url, fragment);
+	// A single instance is the entry itself, so the list is only kept when there are more
+	if let Acs::Vanilla {
+		instances: entry_instances,
+		..
+	} = &mut vanilla_acs
+	{
+		if instances.len() > 1 {
+			*entry_instances = instances;
+		}
+	}
+
+	branch.translate_classes(&mut vanilla_acs);
+	create_acs(vanilla_acs).map(Some)
+}
@@ -0,0 +1,113 @@
+use crate::cache::{CachedPage, PageCache};
+use crate::http::HttpClient;
+use anyhow::Result;
+use log::{debug, error};
+use once_cell::sync::Lazy;
+use rand::Rng;
+use regex::Regex;
+use reqwest::header::{HeaderMap, HeaderValue};
+use scraper::Html;
+use serde_json::Value;
+
+static PAGE_ID_RE: Lazy<Regex> =
+	Lazy::new(|| Regex::new(r"WIKIREQUEST\.info\.pageId\s*=\s*(\d+)").unwrap());
+
+// Wikidot only checks that the token in the form matches the one in the cookie
+pub fn token() -> String {
+	rand::thread_rng()
+		.sample_iter(&rand::distributions::Alphanumeric)
+		.take(8)
+		.map(char::from)
+		.collect()
+}
+
+// Wikidot sets the page id in an inline script of every page
+pub fn parse_page_id(body: &str) -> Option<String> {
+	PAGE_ID_RE.captures(body).map(|cap| cap[1].to_string())
+}
+
+// The HTML inside the JSON returned by the ajax module connector
+pub fn parse_module_body(body: &str) -> Result<Option<Html>> {
+	let json: Value = serde_json::from_str(body)?;
+	match json.get("body") {
+		Some(html_body) => {
+			let html_body_str = html_body
+				.as_str()
+				.ok_or(anyhow::anyhow!("Failed to convert html_body to str"))?;
+			Ok(Some(Html::parse_document(html_body_str)))
+		}
+		None => {
+			error!("No HTML body");
+			Ok(None)
+		}
+	}
+}
+
+// Runs a module of the site's ajax module connector for a page, e.g.
+// backlinks/BacklinksModule or viewsource/ViewSourceModule. With refresh, a cached
+// response is fetched again, e.g. for a page that changed since it was cached
+pub async fn request_module(
+	http: &HttpClient,
+	cache: &PageCache,
+	site_url: &str,
+	module_name: &str,
+	page_id: &str,
+	token: &str,
+	refresh: bool,
+) -> Result<Option<Html>> {
+	let url = format!(
+		"{}/ajax-module-connector.php",
+		site_url.trim_end_matches('/')
+	);
+	let mut headers = HeaderMap::new();
+	headers.insert(
+		"Cookie",
+		HeaderValue::from_str(&format!("wikidot_token7={}", token))?,
+	);
+	let params = [
+		("page_id", page_id),
+		("moduleName", module_name),
+		("callbackIndex", "1"),
+		("wikidot_token7", token),
+	];
+
+	debug!("Created Params for page_id: {} {:?}", page_id, params);
+
+	// The token changes every run, so it is left out of the cache key
+	let request = format!("POST {} {} page_id={}", url, module_name, page_id);
+	let fetch = || async {
+		let response = http.post_form(&url, &headers, &params).await?;
+		Ok(CachedPage::from_response(&request, response))
+	};
+	let response = if refresh {
+		cache.refresh(&request, fetch).await?
+	} else {
+		cache.fetch(&request, fetch).await?
+	};
+
+	match response {
+		Some(response) if response.is_success() => {
+			debug!("{} successful for page_id: {}", module_name, page_id);
+			parse_module_body(&response.body)
+		}
+		Some(response) => {
+			error!(
+				"Failed {} request for page_id {}: {:?}",
+				module_name, page_id, response.status
+			);
+			Ok(None)
+		}
+		None => Ok(None),
+	}
+}
+
+#[test]
+fn test_parse_page_id() {
+	let body = r#"<script type="text/javascript">
+		WIKIREQUEST.info.siteId = 66711;
+		WIKIREQUEST.info.pageId = 858310940;
+		WIKIREQUEST.info.pageUnixName = "component:anomaly-class-bar-source";
+	</script>"#;
+	assert_eq!(parse_page_id(body).as_deref(), Some("858310940"));
+	assert_eq!(parse_page_id("<p>No script</p>"), None);
+}
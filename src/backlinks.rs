@@ -1,132 +1,29 @@
+use crate::acs::BacklinksInfo;
+use crate::ajax;
+use crate::cache::{CachedPage, PageCache};
+use crate::designation::SCPDesignation;
+use crate::error::{ErrorReport, ScrapeError};
+use crate::http::HttpClient;
+use crate::journal::Journal;
+use crate::names::NameIndex;
+use crate::scraper::ScraperConfig;
 use anyhow::Result;
+use futures::stream::{self, StreamExt};
 use indicatif::{ProgressBar, ProgressStyle};
 use log::{debug, error};
 use once_cell::sync::Lazy;
-use rand::Rng;
 use regex::{Regex, RegexSet};
-use reqwest::header::{HeaderMap, HeaderValue};
-use reqwest::Client;
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
-use serde_json::{json, Value};
-use std::{
-	fs::OpenOptions,
-	io::{self, Read, Seek},
-};
-use tokio::{fs, sync::Semaphore, task};
-
-#[derive(Serialize, Deserialize)]
-struct BacklinksInfo {
-	fragment: bool,
-	name: String,
-	actual_number: String,
-	url: String,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-struct SCPInfo {
-	actual_number: String,
-	display_number: String,
-	name: String,
-	url: String,
-}
-
-static SEMAPHORE: Lazy<Semaphore> = Lazy::new(|| Semaphore::new(30));
-static SCP_NUM_RE: Lazy<Regex> =
-	Lazy::new(|| Regex::new(r"(?i)\bscp-([0-9]{1,4})$").unwrap());
-
-fn format_number(number: u16, skip_zero_one: bool) -> String {
-	if (number > 1 || !skip_zero_one) && number <= 99 {
-		format!("SCP-{:03}", number)
-	} else if number > 99 {
-		format!("SCP-{}", number)
-	} else {
-		number.to_string()
-	}
-}
-
-fn clear_file(file_path: &str) -> Result<()> {
-	OpenOptions::new()
-		.write(true)
-		.create(true)
-		.truncate(true)
-		.open(file_path)?;
-	Ok(())
-}
-
-async fn append_json_to_file(json: &Value, file_path: &str) -> Result<()> {
-	let json = json.clone();
-	let file_path = file_path.to_owned();
-
-	task::spawn_blocking(move || {
-		let mut file = OpenOptions::new()
-			.read(true)
-			.write(true)
-			.create(true)
-			.open(&file_path)?;
-
-		let mut contents = String::new();
-		file.read_to_string(&mut contents)?;
-
-		let mut existing_json =
-			serde_json::from_str(&contents).unwrap_or_else(|_| Value::Array(Vec::new()));
-
-		if let Some(array) = existing_json.as_array_mut() {
-			if let Some(new_array) = json.as_array() {
-				array.extend_from_slice(new_array);
-			} else {
-				array.push(json.clone());
-			}
-		}
-
-		file.seek(io::SeekFrom::Start(0))?;
-		file.set_len(0)?;
-
-		serde_json::to_writer_pretty(io::BufWriter::new(&file), &existing_json)?;
-
-		Ok::<(), anyhow::Error>(())
-	})
-	.await??;
-
-	Ok(())
-}
-
-async fn request_page(url: &str) -> Result<Html> {
-	let body = reqwest::get(url).await?.text().await?;
-	Ok(Html::parse_document(&body))
-}
-
-async fn get_scp_name(actual_number: &str) -> Result<String> {
-	let json_data = fs::read_to_string("output/scp_names.json").await?;
-	let scp_names_vec: Vec<SCPInfo> = serde_json::from_str(&json_data)?;
-
-	let scp_name = scp_names_vec
-		.iter()
-		.find(|&scp| scp.actual_number == actual_number)
-		.map(|scp| scp.name.to_owned())
-		.unwrap_or_else(|| actual_number.to_string());
-
-	Ok(scp_name)
-}
-
-fn extract_scp_number(scp_str: &str) -> Option<u16> {
-	let cap = SCP_NUM_RE.captures(scp_str)?;
-	let number = cap[1].parse::<u16>().ok()?;
-	Some(number)
-}
-
-async fn parse_html_to_json(html_body: &Html, page_name: &str) -> Result<Value> {
-	let document = html_body;
-	let _permit = SEMAPHORE.acquire().await;
-
-	let link_selector =
-		Selector::parse("ul li a:first-of-type").expect("Failed to create link Selector");
-	let breadcrumb_selector = Selector::parse("#breadcrumbs > a:last-of-type")
-		.expect("Failed to create link Selector");
-
-	let mut links: Vec<Value> = Vec::new();
-	let re = Regex::new(r" \(/\S+\)").unwrap();
-	let regex_set = RegexSet::new([
+use std::collections::{HashMap, HashSet, VecDeque};
+
+static LINK_SELECTOR: Lazy<Selector> =
+	Lazy::new(|| Selector::parse("ul li a:first-of-type").unwrap());
+static BREADCRUMB_SELECTOR: Lazy<Selector> =
+	Lazy::new(|| Selector::parse("#breadcrumbs > a:last-of-type").unwrap());
+static NAME_URL_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r" \(/\S+\)").unwrap());
+static EXCLUDED_RE: Lazy<RegexSet> = Lazy::new(|| {
+	RegexSet::new([
 		r"(?i)http",
 		r"(?i)component",
 		r"(?i)guide",
@@ -139,45 +36,74 @@ async fn parse_html_to_json(html_body: &Html, page_name: &str) -> Result<Value>
 		r"(?i)resource",
 		r"(?i)theme",
 	])
-	.unwrap();
-
-	let total_entries: u16 = document
-		.select(&link_selector)
-		.filter(|element| {
-			let url = element.value().attr("href").unwrap_or_default();
-			!regex_set.is_match(url)
+	.unwrap()
+});
+// Also matches the designations of the branches, like scp-001-fr or scp-cn-1000
+static SCP_NUM_RE: Lazy<Regex> = Lazy::new(|| {
+	Regex::new(r"(?i)\bscp-(?:[a-z]{2,3}-)?([0-9]{1,4})(?:-[a-z]{1,3})?$").unwrap()
+});
+
+// Fetches a page through the cache. A 404 or an error status that outlasted the retries
+// is an error, so its body is never parsed as the page
+async fn fetch_page(
+	http: &HttpClient,
+	cache: &PageCache,
+	url: &str,
+) -> Result<CachedPage, ScrapeError> {
+	let request = format!("GET {}", url);
+	let page = cache
+		.fetch(&request, || async {
+			Ok(CachedPage::from_response(&request, http.get(url).await?))
 		})
-		.count() as u16;
-	let mut fragments = 0;
-	let mut normal = 0;
-	let backlinks_pb = ProgressBar::new_spinner();
-	backlinks_pb.set_style(
-		ProgressStyle::default_bar()
-			.template("{msg} {spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos:>7}/{len:7} ({eta_precise})")? 
-			.progress_chars("##-")
-	);
-	let message = format!(
-		"Fetching ACS Backlinks Info from {} - Fragments: {} / Normal {}",
-		page_name, fragments, normal
-	);
-	backlinks_pb.set_message(message);
-	backlinks_pb.set_length(total_entries.into());
+		.await
+		.map_err(|e| ScrapeError::from_request(url, e))?
+		.ok_or_else(|| ScrapeError::NotCached {
+			url: url.to_string(),
+		})?;
+
+	if page.is_not_found() {
+		return Err(ScrapeError::NotFound {
+			url: url.to_string(),
+		});
+	}
+	if !page.is_success() {
+		return Err(ScrapeError::Status {
+			url: url.to_string(),
+			status: page.status,
+		});
+	}
+	Ok(page)
+}
 
-	for element in document.select(&link_selector) {
-		let url: String = element.value().attr("href").unwrap_or_default().to_string();
-		let is_fragment: bool = url.contains("fragment:");
+async fn request_page(
+	http: &HttpClient,
+	cache: &PageCache,
+	url: &str,
+) -> Result<Html, ScrapeError> {
+	let page = fetch_page(http, cache, url).await?;
+	Ok(Html::parse_document(&page.body))
+}
 
-		if is_fragment {
-			fragments += 1;
-		} else {
-			normal += 1;
-		}
+fn get_scp_name(names: &NameIndex, actual_number: &str) -> String {
+	names
+		.by_number(actual_number)
+		.map(|scp| scp.name.to_owned())
+		.unwrap_or_else(|| actual_number.to_string())
+}
 
-		let message = format!(
-			"Fetching ACS Backlinks Info from {} - Fragments: {} / Normal {}",
-			page_name, fragments, normal
-		);
-		backlinks_pb.set_message(message);
+fn extract_scp_number(scp_str: &str) -> Option<SCPDesignation> {
+	let matched = SCP_NUM_RE.find(scp_str)?;
+	SCPDesignation::find(matched.as_str())
+}
+
+// Parses the links listed in a BacklinksModule response of the site, before any names are
+// looked up
+pub fn parse_backlinks(document: &Html, site_url: &str) -> Vec<BacklinksInfo> {
+	let mut links: Vec<BacklinksInfo> = Vec::new();
+
+	for element in document.select(&LINK_SELECTOR) {
+		let url: String = element.value().attr("href").unwrap_or_default().to_string();
+		let is_fragment: bool = url.contains("fragment:");
 
 		let name_text = element.text().collect::<Vec<_>>().join("");
 		let mut name = name_text.trim().to_string();
@@ -185,32 +111,15 @@ async fn parse_html_to_json(html_body: &Html, page_name: &str) -> Result<Value>
 
 		debug!("Initial name: {}", name);
 
-		if regex_set.is_match(&url) || regex_set.is_match(&name) {
+		if EXCLUDED_RE.is_match(&url) || EXCLUDED_RE.is_match(&name) {
 			continue;
 		}
 
-		name = re.replace_all(&name, "").to_string();
+		name = NAME_URL_RE.replace_all(&name, "").to_string();
 
 		if SCP_NUM_RE.is_match(&url) && !is_fragment {
-			if let Some(raw_number) = extract_scp_number(&url) {
-				actual_number = format_number(raw_number, false);
-
-				match get_scp_name(&actual_number).await {
-					Ok(name_from_json) => {
-						name = name_from_json;
-						debug!(
-							"SCP Number: {} | Name from json: {}",
-							actual_number, name
-						);
-					}
-					Err(e) => {
-						error!(
-							"Error getting name for SCP Number: {}: {}",
-							actual_number, e
-						);
-						continue;
-					}
-				}
+			if let Some(designation) = extract_scp_number(&url) {
+				actual_number = designation.to_string();
 			}
 		} else if name.len() <= 1 {
 			debug!("SCP URL: {} | Name <= 1: {}", url, name);
@@ -224,163 +133,427 @@ async fn parse_html_to_json(html_body: &Html, page_name: &str) -> Result<Value>
 			}
 		}
 
-		if is_fragment {
-			let page_url = format!("https://scp-wiki.wikidot.com{}/norender/true", url);
-			let document = request_page(&page_url).await?;
-			let mut breadcrumb = document.select(&breadcrumb_selector);
-			if let Some(first_breadcrumb) = breadcrumb.next() {
-				let breadcrumb_text = first_breadcrumb.text().collect::<String>();
-				let breadcrumb_match = SCP_NUM_RE.is_match(&breadcrumb_text);
-				debug!(
-					"breadcrumb text: {} | matches SCP_RUM_RE: {}",
-					breadcrumb_text, breadcrumb_match
-				);
-				if SCP_NUM_RE.is_match(&breadcrumb_text) {
-					if let Some(raw_number) = extract_scp_number(&breadcrumb_text) {
-						actual_number = format_number(raw_number, false);
-						match get_scp_name(&actual_number).await {
-							Ok(name_from_json) => {
-								name = name_from_json;
-								debug!(
-									"SCP Number: {} | Name from json: {}",
-									actual_number, name
-								);
-							}
-							Err(e) => {
-								error!(
-									"Error getting name for SCP Number: {}: {}",
-									actual_number, e
-								);
-								continue;
-							}
-						}
-					}
-				} else {
-					name = breadcrumb_text
-				}
-			}
-		}
+		let mut link = BacklinksInfo {
+			fragment: is_fragment,
+			name,
+			actual_number,
+			url: format!("{}{}", site_url.trim_end_matches('/'), url),
+			include_path: Vec::new(),
+		};
+		apply_proposal_number(&mut link);
 
-		if name.as_str().to_lowercase().contains("proposal")
-			|| url.as_str().to_lowercase().contains("proposal")
-		{
-			actual_number = "SCP-001".to_string();
+		if !links.iter().any(|existing| existing.url == link.url) {
+			links.push(link);
 		}
+	}
+
+	links
+}
 
-		debug!("Final name: {}, Final number: {}", name, actual_number);
+// The component and theme pages listed in a BacklinksModule response. These wrap the
+// component, so the pages including them include it too
+pub fn parse_include_pages(document: &Html) -> Vec<BacklinkComponent> {
+	let mut pages: Vec<BacklinkComponent> = Vec::new();
 
-		if !links.iter().any(|link| link["url"] == url.as_str()) {
-			links.push(json!({
-				"actual_number": actual_number,
-				"name": name,
-				"url": format!("https://scp-wiki.wikidot.com{}", url.as_str()),
-				"fragment": is_fragment
-			}));
+	for element in document.select(&LINK_SELECTOR) {
+		let url = element.value().attr("href").unwrap_or_default();
+		let slug = url.trim_start_matches('/');
+		let lowercase = slug.to_lowercase();
+		if !lowercase.starts_with("component:") && !lowercase.starts_with("theme:") {
+			continue;
 		}
 
-		backlinks_pb.inc(1);
+		if !pages.iter().any(|page| page.slug == slug) {
+			let name = element.text().collect::<String>();
+			pages.push(BacklinkComponent::new(
+				slug,
+				NAME_URL_RE.replace_all(name.trim(), "").as_ref(),
+			));
+		}
 	}
 
-	backlinks_pb.finish();
-	Ok(Value::Array(links))
+	pages
+}
+
+// Parses the JSON returned by the ajax module connector
+pub fn parse_backlinks_response(
+	body: &str,
+	site_url: &str,
+) -> Result<Vec<BacklinksInfo>> {
+	Ok(ajax::parse_module_body(body)?
+		.map(|document| parse_backlinks(&document, site_url))
+		.unwrap_or_default())
 }
 
-#[tokio::main]
-pub async fn fetch_backlinks() -> Result<()> {
-	if pretty_env_logger::try_init().is_err() {
-		log::warn!("Logger is already initialized.");
+// A component page whose backlinks are the pages using that component
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BacklinkComponent {
+	// The page's name on the wiki, e.g. component:anomaly-class-bar-source
+	pub slug: String,
+	pub name: String,
+	// Wikidot's numeric id of the page, read from the page itself when not given
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub page_id: Option<String>,
+}
+
+impl BacklinkComponent {
+	pub fn new(slug: &str, name: &str) -> Self {
+		BacklinkComponent {
+			slug: slug.to_string(),
+			name: name.to_string(),
+			page_id: None,
+		}
 	}
 
-	let token: String = rand::thread_rng()
-		.sample_iter(&rand::distributions::Alphanumeric)
-		.take(8)
-		.map(char::from)
-		.collect();
+	// For pages whose id is already known, so no request is needed to find it
+	pub fn with_page_id(mut self, page_id: &str) -> Self {
+		self.page_id = Some(page_id.to_string());
+		self
+	}
 
-	debug!("Created token: {}", token);
+	pub fn url(&self, site_url: &str) -> String {
+		format!(
+			"{}/{}",
+			site_url.trim_end_matches('/'),
+			self.slug.trim_start_matches('/')
+		)
+	}
+}
 
-	let mut headers = HeaderMap::new();
-	headers.insert(
-		"Cookie",
-		HeaderValue::from_str(&format!("wikidot_token7={}", token)).unwrap(),
-	);
-	headers.insert(
-		"User-Agent",
-		HeaderValue::from_static("reqwest/0.11.20 (rust)"),
-	);
+pub fn default_backlink_components() -> Vec<BacklinkComponent> {
+	vec![
+		BacklinkComponent::new("component:anomaly-class-bar-source", "ACS Bar")
+			.with_page_id("858310940"),
+		BacklinkComponent::new("component:flops-header", "Flops Header")
+			.with_page_id("1058262511"),
+		BacklinkComponent::new(
+			"component:advanced-information-methodology",
+			"AIM Component",
+		)
+		.with_page_id("1307058244"),
+		BacklinkComponent::new("component:acs-hybrid", "ACS Hybrid Bar"),
+		BacklinkComponent::new("component:anomaly-class-bar-lite", "ACS Lite Bar"),
+	]
+}
 
-	debug!("Created headers: {:?}", &headers);
+async fn resolve_page_id(
+	http: &HttpClient,
+	cache: &PageCache,
+	site_url: &str,
+	component: &BacklinkComponent,
+) -> Result<String, ScrapeError> {
+	if let Some(page_id) = &component.page_id {
+		return Ok(page_id.clone());
+	}
 
-	let page_ids = ["858310940", "1058262511", "1307058244"];
+	let url = component.url(site_url);
+	let page = fetch_page(http, cache, &url).await?;
+	ajax::parse_page_id(&page.body).ok_or_else(|| ScrapeError::Parse {
+		url,
+		message: "No page_id found".to_string(),
+	})
+}
 
-	let client = Client::new();
+// The page a fragment belongs to, taken from its breadcrumbs
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FragmentParent {
+	pub name: String,
+	pub actual_number: Option<String>,
+}
 
-	clear_file("output/acs_backlinks.json")?;
+pub fn parse_fragment_parent(document: &Html) -> Option<FragmentParent> {
+	let breadcrumb = document.select(&BREADCRUMB_SELECTOR).next()?;
+	let breadcrumb_text = breadcrumb.text().collect::<String>();
+	let actual_number =
+		extract_scp_number(&breadcrumb_text).map(|designation| designation.to_string());
+	debug!(
+		"breadcrumb text: {} | matches SCP_NUM_RE: {}",
+		breadcrumb_text,
+		actual_number.is_some()
+	);
 
-	for page_id in &page_ids {
-		let params = [
-			("page_id", *page_id),
-			("moduleName", "backlinks/BacklinksModule"),
-			("callbackIndex", "1"),
-			("wikidot_token7", &token),
-		];
+	Some(FragmentParent {
+		name: breadcrumb_text,
+		actual_number,
+	})
+}
 
-		let page_name: String = {
-			if page_id.eq_ignore_ascii_case("858310940") {
-				"ACS Bar".to_string()
-			} else if page_id.eq_ignore_ascii_case("1058262511") {
-				"Flops Header".to_string()
-			} else if page_id.eq_ignore_ascii_case("1307058244") {
-				"AIM Component".to_string()
-			} else {
-				String::new()
-			}
-		};
+// SCP-001 proposals are all filed under SCP-001
+fn apply_proposal_number(link: &mut BacklinksInfo) {
+	if link.name.to_lowercase().contains("proposal")
+		|| link.url.to_lowercase().contains("proposal")
+	{
+		link.actual_number = "SCP-001".to_string();
+	}
+}
 
+// Looks up the name of a link, fetching a fragment's page to resolve it to its parent. A
+// fragment page that fails is recorded in `errors` and the link kept as it was parsed
+async fn resolve_backlink(
+	http: &HttpClient,
+	cache: &PageCache,
+	names: &NameIndex,
+	errors: &ErrorReport,
+	mut link: BacklinksInfo,
+) -> BacklinksInfo {
+	if !link.fragment && SCP_NUM_RE.is_match(&link.url) {
+		link.name = get_scp_name(names, &link.actual_number);
 		debug!(
-			"Created Params from page {} with page_id: {} {:?}",
-			&page_name, &page_id, params
+			"SCP Number: {} | Name from json: {}",
+			link.actual_number, link.name
 		);
+	}
 
-		let response = client
-			.post("https://scp-wiki.wikidot.com/ajax-module-connector.php")
-			.headers(headers.clone())
-			.form(&params)
-			.send()
-			.await?;
+	if link.fragment {
+		let page_url = format!("{}/norender/true", link.url);
+		match request_page(http, cache, &page_url).await {
+			Ok(document) => {
+				if let Some(parent) = parse_fragment_parent(&document) {
+					match parent.actual_number {
+						Some(actual_number) => {
+							link.name = get_scp_name(names, &actual_number);
+							link.actual_number = actual_number;
+							debug!(
+								"SCP Number: {} | Name from json: {}",
+								link.actual_number, link.name
+							);
+						}
+						None => link.name = parent.name,
+					}
+				}
+			}
+			Err(e) => {
+				error!("Keeping the fragment {} unresolved: {}", link.url, e);
+				errors.record(&e);
+			}
+		}
+	}
 
-		if response.status().is_success() {
-			debug!(
-				"Response successful from page {} with page_id: {}",
-				&page_name, &page_id
+	apply_proposal_number(&mut link);
+
+	debug!(
+		"Final name: {}, Final number: {}",
+		link.name, link.actual_number
+	);
+
+	link
+}
+
+// Resolves the parsed links of a component page, fetching up to `limit` fragment pages at
+// once. The links keep their order
+async fn resolve_backlinks(
+	http: &HttpClient,
+	cache: &PageCache,
+	names: &NameIndex,
+	errors: &ErrorReport,
+	links: Vec<BacklinksInfo>,
+	page_name: &str,
+	limit: usize,
+) -> Result<Vec<BacklinksInfo>> {
+	let fragments = links.iter().filter(|link| link.fragment).count();
+	let normal = links.len() - fragments;
+	let backlinks_pb = ProgressBar::new(links.len() as u64);
+	backlinks_pb.set_style(
+		ProgressStyle::default_bar()
+			.template("{msg} {spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos:>7}/{len:7} ({eta_precise})")?
+			.progress_chars("##-")
+	);
+	backlinks_pb.set_message(format!(
+		"Fetching ACS Backlinks Info from {} - Fragments: {} / Normal {}",
+		page_name, fragments, normal
+	));
+
+	let resolved = stream::iter(links)
+		.map(|link| {
+			let backlinks_pb = backlinks_pb.clone();
+			async move {
+				let link = resolve_backlink(http, cache, names, errors, link).await;
+				backlinks_pb.inc(1);
+				link
+			}
+		})
+		.buffered(limit.max(1))
+		.collect::<Vec<_>>()
+		.await;
+
+	backlinks_pb.finish();
+	Ok(resolved)
+}
+
+// Collects the backlinks of a component, then those of the component and theme pages
+// including it, up to `backlink_depth` wrappers deep. Every page keeps the path it was
+// first found through. A page that fails is skipped and recorded in `errors`, so the
+// backlinks of the others are kept
+async fn collect_backlinks(
+	http: &HttpClient,
+	cache: &PageCache,
+	names: &NameIndex,
+	config: &ScraperConfig,
+	errors: &ErrorReport,
+	component: &BacklinkComponent,
+	token: &str,
+) -> Result<Vec<BacklinksInfo>> {
+	let mut queue = VecDeque::from([(component.clone(), vec![component.slug.clone()])]);
+	let mut visited = HashSet::from([component.slug.to_lowercase()]);
+	let mut seen_urls: HashSet<String> = HashSet::new();
+	let mut collected: Vec<BacklinksInfo> = Vec::new();
+
+	while let Some((page, path)) = queue.pop_front() {
+		let skip = |error: ScrapeError| {
+			error!(
+				"Skipping {}, found through {}: {}",
+				page.slug,
+				path.join(" > "),
+				error
 			);
-			let json: Value = response.json().await?;
-			let html: Option<Html> = match json.get("body") {
-				Some(html_body) => {
-					let html_body_str = html_body
-						.as_str()
-						.ok_or(anyhow::anyhow!("Failed to convert html_body to str"))?;
-					let html = Html::parse_document(html_body_str);
-					Some(html)
-				}
-				None => {
-					error!("No HTML body");
-					None
+			errors.record(&error);
+		};
+
+		let page_id =
+			match resolve_page_id(http, cache, &config.branch.site_url, &page).await {
+				Ok(page_id) => page_id,
+				Err(e) => {
+					skip(e);
+					continue;
 				}
 			};
-			if let Some(html) = html {
-				debug!("Parsing page {} with page_id: {}", &page_name, &page_id);
-				let parsed_json = parse_html_to_json(&html, &page_name).await?;
-				append_json_to_file(&parsed_json, "output/acs_backlinks.json").await?;
+		let document = match ajax::request_module(
+			http,
+			cache,
+			&config.branch.site_url,
+			"backlinks/BacklinksModule",
+			&page_id,
+			token,
+			false,
+		)
+		.await
+		{
+			Ok(Some(document)) => document,
+			Ok(None) => continue,
+			Err(e) => {
+				skip(ScrapeError::from_request(
+					&page.url(&config.branch.site_url),
+					e,
+				));
+				continue;
 			}
-		} else {
-			error!(
-				"Failed request or response for page_id {}: {:?}",
-				&page_id,
-				response.status()
+		};
+
+		if path.len() <= config.backlink_depth {
+			for wrapper in parse_include_pages(&document) {
+				if visited.insert(wrapper.slug.to_lowercase()) {
+					debug!("Following {} included by {}", wrapper.slug, page.slug);
+					let mut wrapper_path = path.clone();
+					wrapper_path.push(wrapper.slug.clone());
+					queue.push_back((wrapper, wrapper_path));
+				}
+			}
+		}
+
+		let links = parse_backlinks(&document, &config.branch.site_url)
+			.into_iter()
+			.filter(|link| seen_urls.insert(link.url.clone()))
+			.collect();
+		let links = resolve_backlinks(
+			http,
+			cache,
+			names,
+			errors,
+			links,
+			&page.name,
+			config.limit.into(),
+		)
+		.await?;
+		collected.extend(links.into_iter().map(|mut link| {
+			link.include_path = path.clone();
+			link
+		}));
+	}
+
+	Ok(collected)
+}
+
+// Fetches the backlinks of every configured component. Components found in `done` were
+// finished by an interrupted run and are taken from there, every other one is recorded
+// in the journal
+pub async fn fetch_backlinks(
+	http: &HttpClient,
+	cache: &PageCache,
+	names: &NameIndex,
+	config: &ScraperConfig,
+	errors: &ErrorReport,
+	journal: &Journal,
+	done: &HashMap<String, Vec<BacklinksInfo>>,
+) -> Result<Vec<BacklinksInfo>> {
+	let token = ajax::token();
+
+	debug!("Created token: {}", token);
+
+	let mut backlinks: Vec<BacklinksInfo> = Vec::new();
+
+	for component in &config.backlink_components {
+		if let Some(links) = done.get(&component.slug) {
+			debug!(
+				"Resuming with {} backlinks from {}",
+				links.len(),
+				component.slug
 			);
+			backlinks.extend(links.iter().cloned());
+			continue;
 		}
+
+		let links =
+			collect_backlinks(http, cache, names, config, errors, component, &token)
+				.await?;
+		journal.checkpoint(&component.slug, &links);
+		backlinks.extend(links);
 	}
 
-	Ok(())
+	Ok(backlinks)
+}
+
+#[test]
+fn test_default_backlink_components() {
+	let components: Vec<(String, Option<String>)> = default_backlink_components()
+		.into_iter()
+		.map(|component| (component.slug, component.page_id))
+		.collect();
+	let known = |slug: &str, page_id: &str| (slug.to_string(), Some(page_id.to_string()));
+	assert_eq!(
+		components,
+		vec![
+			known("component:anomaly-class-bar-source", "858310940"),
+			known("component:flops-header", "1058262511"),
+			known("component:advanced-information-methodology", "1307058244"),
+			("component:acs-hybrid".to_string(), None),
+			("component:anomaly-class-bar-lite".to_string(), None),
+		]
+	);
+}
+
+#[test]
+fn test_backlink_component_url() {
+	let component = BacklinkComponent::new("component:flops-header", "Flops Header");
+	assert_eq!(
+		component.url("https://scp-wiki.wikidot.com/"),
+		"https://scp-wiki.wikidot.com/component:flops-header"
+	);
+}
+
+#[test]
+fn test_parse_include_pages() {
+	let body = std::fs::read_to_string("tests/fixtures/backlinks/acs_bar.json").unwrap();
+	let document = ajax::parse_module_body(&body).unwrap().unwrap();
+	let pages = parse_include_pages(&document);
+
+	assert_eq!(
+		pages,
+		vec![
+			BacklinkComponent::new(
+				"component:anomaly-class-bar",
+				"Anomaly Classification Bar"
+			),
+			BacklinkComponent::new("theme:acs-dark", "ACS Dark Theme"),
+		]
+	);
 }
@@ -0,0 +1,188 @@
+use crate::acs::Acs;
+use crate::classes::AcsClasses;
+use crate::designation::SCPDesignation;
+use crate::text::clean_text;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// The labels and words the Backup text search looks for, in the branch's language.
+// Matched against the lowercased page text
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Keywords {
+	pub containment: Vec<String>,
+	pub secondary: Vec<String>,
+	pub disruption: Vec<String>,
+	pub risk: Vec<String>,
+	// Disruption classes mentioned on their own, without a label
+	pub disruption_classes: Vec<String>,
+}
+
+impl Default for Keywords {
+	fn default() -> Self {
+		let list = |words: &[&str]| words.iter().map(|word| word.to_string()).collect();
+
+		Keywords {
+			containment: list(&["containment class:"]),
+			secondary: list(&["secondary class:"]),
+			disruption: list(&["disruption class:"]),
+			risk: list(&["risk class:"]),
+			disruption_classes: list(&["vlam", "keneq", "ekhi", "amida"]),
+		}
+	}
+}
+
+// A wiki of the SCP Foundation, the English one or an international branch, and what
+// differs between them
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Branch {
+	pub name: String,
+	pub site_url: String,
+	// Hub pages listing the SCP names. When empty, scp-series-N pages of the site are
+	// discovered until the first one that doesn't exist
+	pub series_urls: Vec<String>,
+	// The designation of the branch's own SCPs, with {} for the number, e.g. SCP-{}-FR
+	// or SCP-CN-{}
+	pub number_format: String,
+	pub keywords: Keywords,
+	// Localised class names and the English class they stand for, e.g. euclide = euclid
+	pub classes: BTreeMap<String, String>,
+}
+
+impl Default for Branch {
+	fn default() -> Self {
+		Branch {
+			name: "en".to_string(),
+			site_url: "https://scp-wiki.wikidot.com".to_string(),
+			series_urls: Vec::new(),
+			number_format: "SCP-{}".to_string(),
+			keywords: Keywords::default(),
+			classes: BTreeMap::new(),
+		}
+	}
+}
+
+impl Branch {
+	// Numbers below 100 are written with three digits
+	pub fn format_number(&self, number: u16) -> String {
+		self.number_format
+			.replacen("{}", &format!("{:03}", number), 1)
+	}
+
+	pub fn designation(&self, number: u16) -> Option<SCPDesignation> {
+		self.format_number(number).parse().ok()
+	}
+
+	// Whether a designation is one of the branch's own SCPs, not a joke, an explained SCP
+	// or another branch's
+	pub fn is_native(&self, designation: &SCPDesignation) -> bool {
+		self.designation(designation.number).is_some_and(|native| {
+			native.prefix == designation.prefix && native.suffix == designation.suffix
+		})
+	}
+
+	// A link on the site, which may already be absolute
+	pub fn url(&self, link: &str) -> String {
+		if link.starts_with("http://") || link.starts_with("https://") {
+			link.to_string()
+		} else {
+			format!("{}{}", self.site_url.trim_end_matches('/'), link)
+		}
+	}
+
+	// The English class a localised class name stands for, raw or cleaned. Anything not in
+	// the table is returned as it is
+	pub fn translate<'a>(&'a self, class: &'a str) -> &'a str {
+		let key = clean_text(class).to_lowercase();
+		self.classes
+			.iter()
+			.find(|(localised, _)| localised.to_lowercase() == key)
+			.map(|(_, english)| english.as_str())
+			.unwrap_or(class)
+	}
+
+	// Maps the classes of an entry to the canonical English ones through the translation
+	// table. Done before create_acs, which keeps them
+	pub fn translate_classes(&self, acs: &mut Acs) {
+		if let Acs::Vanilla {
+			shared,
+			clearance,
+			risk,
+			classes,
+			..
+		} = acs
+		{
+			*classes = AcsClasses::normalize(
+				self.translate(&shared.contain),
+				self.translate(&shared.secondary),
+				self.translate(&shared.disrupt),
+				self.translate(risk),
+				clearance,
+			);
+		}
+	}
+}
+
+#[test]
+fn test_branch() {
+	use crate::classes::{ContainmentClass, RiskClass};
+
+	let english = Branch::default();
+	assert_eq!(english.format_number(2), "SCP-002");
+	assert!(english.is_native(&SCPDesignation::new(173)));
+	assert!(!english.is_native(&SCPDesignation::with_suffix(173, "J")));
+	assert_eq!(
+		english.url("/scp-173"),
+		"https://scp-wiki.wikidot.com/scp-173"
+	);
+
+	let french: Branch = toml::from_str(
+		r#"
+		name = "fr"
+		site_url = "http://fondationscp.wikidot.com"
+		number_format = "SCP-{}-FR"
+
+		[keywords]
+		containment = ["classe de confinement :"]
+
+		[classes]
+		"Sûr" = "safe"
+		euclide = "euclid"
+		"modéré" = "caution"
+		"#,
+	)
+	.unwrap();
+	assert_eq!(french.format_number(1), "SCP-001-FR");
+	assert!(french.is_native(&SCPDesignation::with_suffix(1, "fr")));
+	assert!(!french.is_native(&SCPDesignation::new(1)));
+	assert_eq!(french.keywords.risk, vec!["risk class:"]);
+	assert_eq!(french.translate("SÛR"), "safe");
+	assert_eq!(french.translate("Keter"), "Keter");
+
+	let mut entry: Acs = serde_json::from_value(serde_json::json!({
+		"contain": "Euclide",
+		"secondary": "",
+		"disrupt": "",
+		"scraper": "Backup",
+		"name": "Le Gardien",
+		"actual_number": "SCP-001-FR",
+		"display_number": "SCP-001-FR",
+		"clearance": "",
+		"clearance_text": "",
+		"risk": "Modéré",
+		"url": "http://fondationscp.wikidot.com/scp-001-fr",
+		"fragment": false
+	}))
+	.unwrap();
+	french.translate_classes(&mut entry);
+	// Normalising the entry again keeps the translated classes
+	let entry = crate::acs::create_acs(entry).unwrap();
+	match entry {
+		Acs::Vanilla { classes, .. } => {
+			assert_eq!(classes.contain, Some(ContainmentClass::Euclid));
+			assert_eq!(classes.risk, Some(RiskClass::Caution));
+		}
+		other => panic!("Expected a finished entry, got {:?}", other),
+	}
+}
@@ -0,0 +1,275 @@
+use crate::http::HttpResponse;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+	collections::BTreeMap,
+	future::Future,
+	path::{Path, PathBuf},
+	time::{SystemTime, UNIX_EPOCH},
+};
+use tokio::fs;
+
+// How the page cache is used for a run. Cached pages are only replayed when asked for,
+// a plain run fetches live pages and keeps the cache up to date
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CacheMode {
+	// Serve cached pages, fetch and store the missing ones. Cached 404s are fetched again
+	ReadWrite,
+	// Fetch every page again and overwrite the cached copy
+	#[default]
+	Refresh,
+	// Never touch the network, pages missing from the cache are treated as not found
+	CacheOnly,
+	// Never touch the network, pages missing from the cache are an error
+	Offline,
+	// Always fetch, never read or write the cache
+	Disabled,
+}
+
+impl CacheMode {
+	pub fn uses_network(&self) -> bool {
+		!matches!(self, CacheMode::CacheOnly | CacheMode::Offline)
+	}
+}
+
+// A single response as stored on disk
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CachedPage {
+	pub request: String,
+	pub status: u16,
+	pub headers: BTreeMap<String, String>,
+	pub body: String,
+	pub fetched_at: u64,
+}
+
+impl CachedPage {
+	pub fn from_response(request: &str, response: HttpResponse) -> Self {
+		let headers = response
+			.headers
+			.iter()
+			.filter_map(|(name, value)| {
+				Some((name.to_string(), value.to_str().ok()?.to_string()))
+			})
+			.collect();
+
+		CachedPage {
+			request: request.to_string(),
+			status: response.status.as_u16(),
+			headers,
+			body: response.body,
+			fetched_at: unix_now(),
+		}
+	}
+
+	pub fn is_success(&self) -> bool {
+		(200..300).contains(&self.status)
+	}
+
+	pub fn is_not_found(&self) -> bool {
+		self.status == 404
+	}
+}
+
+pub fn unix_now() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_secs())
+		.unwrap_or_default()
+}
+
+// Content-addressed store of fetched pages. Each request is hashed into a file name so
+// the same URL always maps to the same entry, e.g. `cache/3f/3fa9...json`
+#[derive(Debug, Clone)]
+pub struct PageCache {
+	dir: PathBuf,
+	mode: CacheMode,
+}
+
+impl PageCache {
+	pub fn new(dir: impl AsRef<Path>, mode: CacheMode) -> Self {
+		PageCache {
+			dir: dir.as_ref().to_path_buf(),
+			mode,
+		}
+	}
+
+	pub fn mode(&self) -> CacheMode {
+		self.mode
+	}
+
+	pub fn dir(&self) -> &Path {
+		&self.dir
+	}
+
+	pub fn key(request: &str) -> String {
+		format!("{:x}", Sha256::digest(request.as_bytes()))
+	}
+
+	fn path(&self, request: &str) -> PathBuf {
+		let key = PageCache::key(request);
+		self.dir.join(&key[..2]).join(format!("{}.json", key))
+	}
+
+	pub async fn get(&self, request: &str) -> Result<Option<CachedPage>> {
+		let path = self.path(request);
+		if !fs::try_exists(&path).await? {
+			return Ok(None);
+		}
+
+		let contents = fs::read_to_string(&path).await?;
+		Ok(Some(serde_json::from_str(&contents)?))
+	}
+
+	pub async fn put(&self, page: &CachedPage) -> Result<()> {
+		let path = self.path(&page.request);
+		if let Some(parent) = path.parent() {
+			fs::create_dir_all(parent).await?;
+		}
+
+		let temp_path = path.with_extension("json.tmp");
+		fs::write(&temp_path, serde_json::to_vec(page)?).await?;
+		fs::rename(&temp_path, &path).await?;
+
+		Ok(())
+	}
+
+	// Resolves a request through the cache according to the mode. `request` identifies
+	// the request (method, URL and any parameters that change the response) and `fetch`
+	// performs it over the network. Returns None when a cache-only run has no copy.
+	pub async fn fetch<F, Fut>(
+		&self,
+		request: &str,
+		fetch: F,
+	) -> Result<Option<CachedPage>>
+	where
+		F: FnOnce() -> Fut,
+		Fut: Future<Output = Result<CachedPage>>,
+	{
+		self.fetch_as(self.mode, request, fetch).await
+	}
+
+	// Like fetch, but a cached copy is fetched again unless the run can't use the network
+	pub async fn refresh<F, Fut>(
+		&self,
+		request: &str,
+		fetch: F,
+	) -> Result<Option<CachedPage>>
+	where
+		F: FnOnce() -> Fut,
+		Fut: Future<Output = Result<CachedPage>>,
+	{
+		let mode = match self.mode {
+			CacheMode::ReadWrite => CacheMode::Refresh,
+			mode => mode,
+		};
+		self.fetch_as(mode, request, fetch).await
+	}
+
+	async fn fetch_as<F, Fut>(
+		&self,
+		mode: CacheMode,
+		request: &str,
+		fetch: F,
+	) -> Result<Option<CachedPage>>
+	where
+		F: FnOnce() -> Fut,
+		Fut: Future<Output = Result<CachedPage>>,
+	{
+		if matches!(
+			mode,
+			CacheMode::ReadWrite | CacheMode::CacheOnly | CacheMode::Offline
+		) {
+			match self.get(request).await? {
+				// A missing page may have been created since, e.g. a new series hub
+				Some(page) if mode == CacheMode::ReadWrite && page.is_not_found() => {
+					log::debug!("Cached 404 fetched again: {}", request);
+				}
+				Some(page) => {
+					log::debug!("Cache hit for {}", request);
+					return Ok(Some(page));
+				}
+				None => {}
+			}
+		}
+
+		match mode {
+			CacheMode::CacheOnly => {
+				log::warn!("Not in cache, skipping: {}", request);
+				return Ok(None);
+			}
+			CacheMode::Offline => {
+				return Err(anyhow!("Offline and not in cache: {}", request));
+			}
+			_ => {}
+		}
+
+		let page = fetch().await?;
+
+		// Server errors are not worth replaying
+		if mode != CacheMode::Disabled && (page.is_success() || page.is_not_found()) {
+			self.put(&page).await?;
+		}
+
+		Ok(Some(page))
+	}
+}
+
+impl Default for PageCache {
+	fn default() -> Self {
+		PageCache::new("cache", CacheMode::default())
+	}
+}
+
+#[tokio::test]
+async fn test_page_cache_modes() {
+	let dir = std::env::temp_dir().join(format!("acs-cache-test-{}", std::process::id()));
+	let request = "GET https://scp-wiki.wikidot.com/scp-173";
+	let page = || async {
+		Ok(CachedPage {
+			request: request.to_string(),
+			status: 200,
+			headers: BTreeMap::new(),
+			body: "<p>Euclid</p>".to_string(),
+			fetched_at: unix_now(),
+		})
+	};
+
+	let offline = PageCache::new(&dir, CacheMode::Offline);
+	assert!(offline.fetch(request, page).await.is_err());
+
+	let cache_only = PageCache::new(&dir, CacheMode::CacheOnly);
+	assert!(cache_only.fetch(request, page).await.unwrap().is_none());
+
+	let read_write = PageCache::new(&dir, CacheMode::ReadWrite);
+	read_write.fetch(request, page).await.unwrap();
+
+	let replayed = offline.fetch(request, page).await.unwrap().unwrap();
+	assert_eq!(replayed.body, "<p>Euclid</p>");
+	assert_eq!(replayed.status, 200);
+
+	let missing = "GET https://scp-wiki.wikidot.com/scp-series-10";
+	let status = |status: u16| {
+		move || async move {
+			Ok(CachedPage {
+				request: missing.to_string(),
+				status,
+				headers: BTreeMap::new(),
+				body: String::new(),
+				fetched_at: unix_now(),
+			})
+		}
+	};
+	read_write.fetch(missing, status(404)).await.unwrap();
+	let created = read_write
+		.fetch(missing, status(200))
+		.await
+		.unwrap()
+		.unwrap();
+	assert_eq!(created.status, 200, "A cached 404 is not replayed");
+
+	let fresh = PageCache::default();
+	assert_eq!(fresh.mode(), CacheMode::Refresh);
+
+	std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,372 @@
+use crate::text::clean_text;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+static LEVEL_RGX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\d").unwrap());
+
+// Filler left in the template instead of a class, e.g. "{FIELD LEFT BLANK}" or "N/A"
+fn is_blank(text: &str) -> bool {
+	let text = text
+		.trim()
+		.trim_matches(|c: char| c == '{' || c == '}' || c == '[' || c == ']')
+		.trim();
+	text.is_empty()
+		|| [
+			"none",
+			"n/a",
+			"na",
+			"null",
+			"-",
+			"—",
+			"field left blank",
+			"blank",
+		]
+		.iter()
+		.any(|blank| text.eq_ignore_ascii_case(blank))
+}
+
+// Lowercased words of the text, ignoring numbers and punctuation like "#2 Vlam"
+fn words(text: &str) -> Vec<String> {
+	text.split(|c: char| !c.is_alphabetic())
+		.filter(|word| !word.is_empty())
+		.map(|word| word.to_lowercase())
+		.collect()
+}
+
+// Primary and secondary containment classes. Anything the Foundation doesn't define is
+// kept as `Esoteric` with the class name as written on the page; the bare word
+// "Esoteric" becomes `Esoteric` with an empty name
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", from = "String")]
+pub enum ContainmentClass {
+	Safe,
+	Euclid,
+	Keter,
+	Neutralized,
+	Pending,
+	Explained,
+	Decommissioned,
+	Uncontained,
+	Apollyon,
+	Archon,
+	Cernunnos,
+	Hiemal,
+	Thaumiel,
+	Tiamat,
+	Ticonderoga,
+	Esoteric(String),
+}
+
+impl ContainmentClass {
+	pub fn normalize(raw: &str) -> Option<Self> {
+		let text = clean_text(raw);
+		if is_blank(&text) {
+			return None;
+		}
+
+		let class = match text.to_lowercase().as_str() {
+			"safe" => ContainmentClass::Safe,
+			"euclid" => ContainmentClass::Euclid,
+			"keter" => ContainmentClass::Keter,
+			"neutralized" | "neutralised" => ContainmentClass::Neutralized,
+			"pending" => ContainmentClass::Pending,
+			"explained" => ContainmentClass::Explained,
+			"decommissioned" => ContainmentClass::Decommissioned,
+			"uncontained" => ContainmentClass::Uncontained,
+			"apollyon" => ContainmentClass::Apollyon,
+			"archon" => ContainmentClass::Archon,
+			"cernunnos" => ContainmentClass::Cernunnos,
+			"hiemal" => ContainmentClass::Hiemal,
+			"thaumiel" => ContainmentClass::Thaumiel,
+			"tiamat" => ContainmentClass::Tiamat,
+			"ticonderoga" => ContainmentClass::Ticonderoga,
+			"esoteric" => ContainmentClass::Esoteric(String::new()),
+			_ => ContainmentClass::Esoteric(text),
+		};
+
+		Some(class)
+	}
+
+	pub fn is_esoteric(&self) -> bool {
+		matches!(self, ContainmentClass::Esoteric(_))
+	}
+}
+
+impl fmt::Display for ContainmentClass {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let name = match self {
+			ContainmentClass::Safe => "Safe",
+			ContainmentClass::Euclid => "Euclid",
+			ContainmentClass::Keter => "Keter",
+			ContainmentClass::Neutralized => "Neutralized",
+			ContainmentClass::Pending => "Pending",
+			ContainmentClass::Explained => "Explained",
+			ContainmentClass::Decommissioned => "Decommissioned",
+			ContainmentClass::Uncontained => "Uncontained",
+			ContainmentClass::Apollyon => "Apollyon",
+			ContainmentClass::Archon => "Archon",
+			ContainmentClass::Cernunnos => "Cernunnos",
+			ContainmentClass::Hiemal => "Hiemal",
+			ContainmentClass::Thaumiel => "Thaumiel",
+			ContainmentClass::Tiamat => "Tiamat",
+			ContainmentClass::Ticonderoga => "Ticonderoga",
+			ContainmentClass::Esoteric(name) if name.is_empty() => "Esoteric",
+			ContainmentClass::Esoteric(name) => name,
+		};
+		write!(f, "{}", name)
+	}
+}
+
+impl From<ContainmentClass> for String {
+	fn from(class: ContainmentClass) -> Self {
+		class.to_string()
+	}
+}
+
+impl From<String> for ContainmentClass {
+	fn from(text: String) -> Self {
+		ContainmentClass::normalize(&text).unwrap_or(ContainmentClass::Esoteric(text))
+	}
+}
+
+// Defines a closed set of classes that is matched on any word of the scraped text
+macro_rules! class_enum {
+	($(#[$meta:meta])* $name:ident { $($variant:ident => $word:literal),+ $(,)? }) => {
+		$(#[$meta])*
+		#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+		pub enum $name {
+			$($variant),+
+		}
+
+		impl $name {
+			pub fn normalize(raw: &str) -> Option<Self> {
+				let text = clean_text(raw);
+				if is_blank(&text) {
+					return None;
+				}
+
+				words(&text).into_iter().find_map(|word| match word.as_str() {
+					$($word => Some($name::$variant),)+
+					_ => None,
+				})
+			}
+		}
+
+		impl fmt::Display for $name {
+			fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+				fmt::Debug::fmt(self, f)
+			}
+		}
+	};
+}
+
+class_enum!(
+	// Disruption classes, from least to most disruptive
+	DisruptionClass {
+		Dark => "dark",
+		Vlam => "vlam",
+		Keneq => "keneq",
+		Ekhi => "ekhi",
+		Amida => "amida",
+	}
+);
+
+class_enum!(
+	// Risk classes, from least to most dangerous
+	RiskClass {
+		Notice => "notice",
+		Caution => "caution",
+		Warning => "warning",
+		Danger => "danger",
+		Critical => "critical",
+	}
+);
+
+// Clearance levels 0 to 6, serialized as the level number
+#[derive(
+	Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(into = "u8", try_from = "u8")]
+pub enum ClearanceLevel {
+	ForOfficialUseOnly = 0,
+	Unrestricted = 1,
+	Restricted = 2,
+	Confidential = 3,
+	Secret = 4,
+	TopSecret = 5,
+	CosmicTopSecret = 6,
+}
+
+impl ClearanceLevel {
+	pub fn from_level(level: u8) -> Option<Self> {
+		let level = match level {
+			0 => ClearanceLevel::ForOfficialUseOnly,
+			1 => ClearanceLevel::Unrestricted,
+			2 => ClearanceLevel::Restricted,
+			3 => ClearanceLevel::Confidential,
+			4 => ClearanceLevel::Secret,
+			5 => ClearanceLevel::TopSecret,
+			6 => ClearanceLevel::CosmicTopSecret,
+			_ => return None,
+		};
+		Some(level)
+	}
+
+	// Reads the first digit, e.g. "LEVEL 4" or "4/3787", then falls back to the name
+	pub fn normalize(raw: &str) -> Option<Self> {
+		if let Some(digit) = LEVEL_RGX.find(raw) {
+			return digit
+				.as_str()
+				.parse()
+				.ok()
+				.and_then(ClearanceLevel::from_level);
+		}
+
+		let text = raw.trim().to_lowercase();
+		[
+			ClearanceLevel::CosmicTopSecret,
+			ClearanceLevel::TopSecret,
+			ClearanceLevel::Secret,
+			ClearanceLevel::Confidential,
+			ClearanceLevel::Unrestricted,
+			ClearanceLevel::Restricted,
+			ClearanceLevel::ForOfficialUseOnly,
+		]
+		.into_iter()
+		.find(|level| text.contains(&level.text().to_lowercase()))
+	}
+
+	pub fn level(&self) -> u8 {
+		*self as u8
+	}
+
+	pub fn text(&self) -> &'static str {
+		match self {
+			ClearanceLevel::ForOfficialUseOnly => "For Official Use Only",
+			ClearanceLevel::Unrestricted => "Unrestricted",
+			ClearanceLevel::Restricted => "Restricted",
+			ClearanceLevel::Confidential => "Confidential",
+			ClearanceLevel::Secret => "Secret",
+			ClearanceLevel::TopSecret => "Top Secret",
+			ClearanceLevel::CosmicTopSecret => "Cosmic Top Secret",
+		}
+	}
+}
+
+impl fmt::Display for ClearanceLevel {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "LEVEL {}", self.level())
+	}
+}
+
+impl From<ClearanceLevel> for u8 {
+	fn from(level: ClearanceLevel) -> Self {
+		level.level()
+	}
+}
+
+impl TryFrom<u8> for ClearanceLevel {
+	type Error = String;
+
+	fn try_from(level: u8) -> Result<Self, Self::Error> {
+		ClearanceLevel::from_level(level)
+			.ok_or(format!("Invalid clearance level {}", level))
+	}
+}
+
+// The canonical classes of an entry. The scraped text is kept as is in the entry's
+// string fields, these are None when the text is blank or not a recognised class
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct AcsClasses {
+	pub contain: Option<ContainmentClass>,
+	pub secondary: Option<ContainmentClass>,
+	pub disrupt: Option<DisruptionClass>,
+	pub risk: Option<RiskClass>,
+	pub clearance: Option<ClearanceLevel>,
+}
+
+impl AcsClasses {
+	pub fn normalize(
+		contain: &str,
+		secondary: &str,
+		disrupt: &str,
+		risk: &str,
+		clearance: &str,
+	) -> Self {
+		AcsClasses {
+			contain: ContainmentClass::normalize(contain),
+			secondary: ContainmentClass::normalize(secondary),
+			disrupt: DisruptionClass::normalize(disrupt),
+			risk: RiskClass::normalize(risk),
+			clearance: ClearanceLevel::normalize(clearance),
+		}
+	}
+}
+
+#[test]
+fn test_normalize_classes() {
+	assert_eq!(
+		ContainmentClass::normalize("Keter"),
+		Some(ContainmentClass::Keter)
+	);
+	assert_eq!(
+		ContainmentClass::normalize(" keter "),
+		Some(ContainmentClass::Keter)
+	);
+	assert_eq!(
+		ContainmentClass::normalize("Containment Class: Euclid"),
+		Some(ContainmentClass::Euclid)
+	);
+	assert_eq!(ContainmentClass::normalize("{FIELD LEFT BLANK}"), None);
+	assert_eq!(ContainmentClass::normalize("N/A"), None);
+	assert_eq!(ContainmentClass::normalize("{$secondary-class}"), None);
+	assert_eq!(
+		ContainmentClass::normalize("Maksur"),
+		Some(ContainmentClass::Esoteric("Maksur".to_string()))
+	);
+	assert_eq!(
+		ContainmentClass::normalize("esoteric"),
+		Some(ContainmentClass::Esoteric(String::new()))
+	);
+
+	assert_eq!(
+		DisruptionClass::normalize("2/Vlam"),
+		Some(DisruptionClass::Vlam)
+	);
+	assert_eq!(
+		DisruptionClass::normalize("#4 EKHI"),
+		Some(DisruptionClass::Ekhi)
+	);
+	assert_eq!(DisruptionClass::normalize("Omega"), None);
+	assert_eq!(RiskClass::normalize("critical"), Some(RiskClass::Critical));
+
+	assert_eq!(
+		ClearanceLevel::normalize("LEVEL 4"),
+		Some(ClearanceLevel::Secret)
+	);
+	assert_eq!(
+		ClearanceLevel::normalize("Top Secret"),
+		Some(ClearanceLevel::TopSecret)
+	);
+	assert_eq!(
+		ClearanceLevel::normalize("Unrestricted"),
+		Some(ClearanceLevel::Unrestricted)
+	);
+	assert_eq!(ClearanceLevel::normalize("LEVEL 9"), None);
+
+	let classes = AcsClasses::normalize("esoteric", "Maksur", "keneq", "", "LEVEL 6");
+	let json = serde_json::to_value(&classes).unwrap();
+	assert_eq!(
+		json,
+		serde_json::json!({
+			"contain": "Esoteric",
+			"secondary": "Maksur",
+			"disrupt": "Keneq",
+			"risk": null,
+			"clearance": 6
+		})
+	);
+	assert_eq!(serde_json::from_value::<AcsClasses>(json).unwrap(), classes);
+}
@@ -0,0 +1,343 @@
+use crate::acs::{Acs, SharedAcs};
+use crate::extract::AcsExtractor;
+use crate::instances::FoundInstance;
+use crate::text::{
+	clean_text, clearance_level_conversion, extract_class, extract_text,
+	is_valid_containment_class,
+};
+use anyhow::{anyhow, Context, Result};
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::{BTreeMap, HashMap},
+	path::Path,
+};
+
+// Definitions for the ACS Bar, Hybrid Bar, Flops Header and AIM Header
+const BUILTIN_COMPONENTS: &str = include_str!("components.toml");
+
+#[derive(
+	Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum AcsField {
+	Clearance,
+	ClearanceText,
+	Contain,
+	Secondary,
+	Disrupt,
+	Risk,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Extraction {
+	#[default]
+	Text,
+	Class,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FieldDefinition {
+	pub selector: String,
+
+	#[serde(default)]
+	pub extract: Extraction,
+
+	#[serde(default = "default_clean")]
+	pub clean: bool,
+
+	#[serde(default)]
+	pub map: HashMap<String, String>,
+
+	#[serde(default)]
+	pub clearance_level: bool,
+
+	#[serde(default)]
+	pub ignore: Vec<String>,
+}
+
+fn default_clean() -> bool {
+	true
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ComponentDefinition {
+	pub name: String,
+	pub priority: i32,
+	pub detect: Vec<String>,
+
+	#[serde(default)]
+	pub esoteric_fallback: bool,
+
+	#[serde(default)]
+	pub fields: BTreeMap<AcsField, FieldDefinition>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ComponentSet {
+	#[serde(rename = "component")]
+	pub components: Vec<ComponentDefinition>,
+}
+
+impl ComponentSet {
+	pub fn builtin() -> Self {
+		toml::from_str(BUILTIN_COMPONENTS)
+			.expect("Built-in component definitions are invalid")
+	}
+
+	// Reads a TOML or JSON (by extension) component definitions file
+	pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+		let path = path.as_ref();
+		let contents = std::fs::read_to_string(path).with_context(|| {
+			format!("Failed to read components file {}", path.display())
+		})?;
+
+		let set: ComponentSet = if path.extension().is_some_and(|ext| ext == "json") {
+			serde_json::from_str(&contents)?
+		} else {
+			toml::from_str(&contents)?
+		};
+
+		Ok(set)
+	}
+
+	pub fn get(&self, name: &str) -> Option<&ComponentDefinition> {
+		self.components.iter().find(|c| c.name == name)
+	}
+
+	pub fn extractors(&self) -> Result<Vec<ComponentExtractor>> {
+		self.components
+			.iter()
+			.cloned()
+			.map(ComponentExtractor::new)
+			.collect()
+	}
+}
+
+fn parse_selector(component: &str, selector: &str) -> Result<Selector> {
+	Selector::parse(selector)
+		.map_err(|e| anyhow!("Invalid selector '{}' in {}: {}", selector, component, e))
+}
+
+// An extractor built from a ComponentDefinition
+pub struct ComponentExtractor {
+	definition: ComponentDefinition,
+	detect: Vec<Selector>,
+	fields: Vec<(AcsField, Selector)>,
+}
+
+impl ComponentExtractor {
+	pub fn new(definition: ComponentDefinition) -> Result<Self> {
+		let detect = definition
+			.detect
+			.iter()
+			.map(|selector| parse_selector(&definition.name, selector))
+			.collect::<Result<Vec<_>>>()?;
+		let fields = definition
+			.fields
+			.iter()
+			.map(|(field, def)| {
+				Ok((*field, parse_selector(&definition.name, &def.selector)?))
+			})
+			.collect::<Result<Vec<_>>>()?;
+
+		Ok(ComponentExtractor {
+			definition,
+			detect,
+			fields,
+		})
+	}
+
+	pub fn definition(&self) -> &ComponentDefinition {
+		&self.definition
+	}
+
+	fn extract_field(
+		&self,
+		document: &Html,
+		field: AcsField,
+		selector: &Selector,
+	) -> String {
+		let def = &self.definition.fields[&field];
+		let raw = match def.extract {
+			Extraction::Text => extract_text(document.root_element(), selector),
+			Extraction::Class => extract_class(document.root_element(), selector),
+		}
+		.unwrap_or_default();
+
+		let mut value = if def.clean { clean_text(&raw) } else { raw };
+
+		if !def.map.is_empty() {
+			value = def.map.get(&value).cloned().unwrap_or_default();
+		}
+		if def.clearance_level {
+			value = clearance_level_conversion(&value);
+		}
+		if def.ignore.iter().any(|i| value.eq_ignore_ascii_case(i)) {
+			value = String::new();
+		}
+
+		value
+	}
+
+	// Scrapes every defined field, whether or not the component was detected
+	pub fn extract_fields(&self, document: &Html) -> Acs {
+		let mut values: HashMap<AcsField, String> = self
+			.fields
+			.iter()
+			.map(|(field, selector)| {
+				(*field, self.extract_field(document, *field, selector))
+			})
+			.collect();
+		let mut take = |field| values.remove(&field).unwrap_or_default();
+
+		let mut contain = take(AcsField::Contain);
+		let mut secondary = take(AcsField::Secondary);
+
+		if self.definition.esoteric_fallback && !is_valid_containment_class(&contain) {
+			secondary = contain;
+			contain = "esoteric".to_string();
+		}
+
+		Acs::Bar {
+			shared: SharedAcs {
+				contain,
+				secondary,
+				disrupt: take(AcsField::Disrupt),
+				scraper: self.definition.name.clone(),
+			},
+			clearance: take(AcsField::Clearance),
+			clearance_text: take(AcsField::ClearanceText),
+			risk: take(AcsField::Risk),
+		}
+	}
+}
+
+impl AcsExtractor for ComponentExtractor {
+	fn name(&self) -> &str {
+		&self.definition.name
+	}
+
+	fn priority(&self) -> i32 {
+		self.definition.priority
+	}
+
+	fn detect(&self, document: &Html) -> bool {
+		self.detect
+			.iter()
+			.any(|selector| document.select(selector).next().is_some())
+	}
+
+	fn extract(&self, document: &Html) -> Option<Acs> {
+		Some(self.extract_fields(document))
+	}
+
+	// With several instances on the page, each one is scraped from a copy of the document
+	// without the others, so the field selectors only find its own values. An instance
+	// is its element and the siblings after it up to the next instance, which is where
+	// selectors like `.itemInfo.darkbox + p` look
+	fn extract_all(&self, document: &Html) -> Vec<FoundInstance> {
+		let mut elements: Vec<ElementRef> = Vec::new();
+		for selector in &self.detect {
+			for element in document.select(selector) {
+				if !elements.iter().any(|found| found.id() == element.id()) {
+					elements.push(element);
+				}
+			}
+		}
+		// A component nested in another one is part of it
+		let nested = |element: &ElementRef| {
+			elements.iter().any(|other| {
+				element
+					.ancestors()
+					.any(|ancestor| ancestor.id() == other.id())
+			})
+		};
+		let elements: Vec<ElementRef> = elements
+			.iter()
+			.filter(|element| !nested(element))
+			.copied()
+			.collect();
+
+		// The element of an instance and the siblings following it, up to the first one that
+		// is or holds another instance
+		let instance_nodes = |element: &ElementRef| {
+			let mut nodes = vec![element.id()];
+			nodes.extend(
+				element
+					.next_siblings()
+					.take_while(|sibling| {
+						!sibling.descendants().any(|descendant| {
+							elements
+								.iter()
+								.any(|instance| instance.id() == descendant.id())
+						})
+					})
+					.map(|sibling| sibling.id()),
+			);
+			nodes
+		};
+
+		match elements.as_slice() {
+			[] => Vec::new(),
+			[element] => vec![FoundInstance::new(
+				self.extract_fields(document),
+				document,
+				Some(*element),
+			)],
+			_ => elements
+				.iter()
+				.map(|element| {
+					let mut isolated = document.clone();
+					for other in
+						elements.iter().filter(|other| other.id() != element.id())
+					{
+						for id in instance_nodes(other) {
+							if let Some(mut node) = isolated.tree.get_mut(id) {
+								node.detach();
+							}
+						}
+					}
+					FoundInstance::new(
+						self.extract_fields(&isolated),
+						document,
+						Some(*element),
+					)
+				})
+				.collect(),
+		}
+	}
+}
+
+#[test]
+fn test_builtin_components() {
+	let set = ComponentSet::builtin();
+	let extractors = set.extractors().expect("Built-in selectors should parse");
+	assert_eq!(extractors.len(), 4);
+
+	let aim = extractors
+		.iter()
+		.find(|e| e.name() == "AIM Header")
+		.expect("AIM Header should be built in");
+	let document = Html::parse_document(
+		r#"<div class="desktop-aim"><div class="w-container"><div>
+			<div class="cell-container-image"></div>
+			<div><p><span><span class="three"></span></span></p></div>
+			<div><p>Thaumiel</p></div>
+			<div><p>Keneq</p></div>
+		</div></div></div>"#,
+	);
+	assert!(aim.detect(&document));
+	match aim.extract_fields(&document) {
+		Acs::Bar {
+			shared, clearance, ..
+		} => {
+			assert_eq!(clearance, "LEVEL 3");
+			assert_eq!(shared.contain, "esoteric");
+			assert_eq!(shared.secondary, "Thaumiel");
+			assert_eq!(shared.disrupt, "Keneq");
+		}
+		other => panic!("Unexpected Acs variant {:?}", other),
+	}
+}
@@ -0,0 +1,407 @@
+use crate::backlinks::{default_backlink_components, BacklinkComponent};
+use crate::branch::{Branch, Keywords};
+use crate::components::ComponentSet;
+use crate::designation::SeriesKind;
+use crate::extract::ExtractorRegistry;
+use crate::http::HttpConfig;
+use crate::output::{Artifact, JsonFileSink};
+use crate::retry::RetryPolicy;
+use crate::scraper::ScraperConfig;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::BTreeMap,
+	fs,
+	path::{Path, PathBuf},
+	time::Duration,
+};
+
+// Declares a config section whose fields are all optional, so a layer only overrides
+// the settings it actually sets
+macro_rules! section {
+	($(#[$meta:meta])* $name:ident { $($(#[$field_meta:meta])* $field:ident: $ty:ty,)* }) => {
+		$(#[$meta])*
+		#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+		#[serde(default, deny_unknown_fields)]
+		pub struct $name {
+			$(
+				$(#[$field_meta])*
+				#[serde(skip_serializing_if = "Option::is_none")]
+				pub $field: Option<$ty>,
+			)*
+		}
+
+		impl $name {
+			// Takes every setting the other layer sets
+			pub fn overlay(&mut self, other: $name) {
+				$(
+					if other.$field.is_some() {
+						self.$field = other.$field;
+					}
+				)*
+			}
+		}
+	};
+}
+
+section!(
+	// Which pages are scraped and how
+	ScrapeSettings {
+		start: u16,
+		end: u16,
+		limit: u16,
+		// Hours after which an incremental run fetches a page again
+		max_age: u64,
+		// TOML or JSON file replacing the built-in component definitions
+		components: PathBuf,
+		// Names of the extractors that are run, everything else is skipped
+		extractors: Vec<String>,
+		// Read the ACS arguments from the page source before the rendered HTML
+		source: bool,
+		// Series outside the numbered ones that are added to the names and scraped
+		include: Vec<SeriesKind>,
+	}
+);
+
+section!(
+	// Requests, retries and the page cache
+	NetworkSettings {
+		retries: u32,
+		// Seconds after which a request is no longer retried
+		retry_time: u64,
+		user_agent: String,
+		contact: String,
+		// Requests per second allowed to a single host, 0 disables the limit
+		rate: f64,
+		// How many requests may be sent back to back before the rate applies
+		burst: u32,
+		// Seconds before a request times out
+		timeout: u64,
+		// Seconds before connecting to a host times out
+		connect_timeout: u64,
+		cache_dir: PathBuf,
+	}
+);
+
+section!(
+	// The output directory and the file names inside it
+	OutputSettings {
+		dir: PathBuf,
+		names: PathBuf,
+		database: PathBuf,
+		backlinks: PathBuf,
+		metadata: PathBuf,
+	}
+);
+
+section!(
+	// The wiki that is scraped, the English one unless set
+	BranchSettings {
+		name: String,
+		site_url: String,
+		// Discovered from the site's scp-series-N pages when not set
+		series_urls: Vec<String>,
+		// The designation of the branch's SCPs, with {} for the number
+		number_format: String,
+		// The labels the Backup text search looks for
+		keywords: Keywords,
+		// Localised class names and the English class they stand for
+		classes: BTreeMap<String, String>,
+	}
+);
+
+section!(
+	BacklinksSettings {
+		components: Vec<BacklinkComponent>,
+		// How many component or theme pages deep the wrappers of a component are followed
+		depth: usize,
+	}
+);
+
+// The settings of a run. Built from layers, each overriding the one below it: the
+// built-in defaults, the acs.toml file, environment variables and command line flags
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+	pub scrape: ScrapeSettings,
+	pub network: NetworkSettings,
+	pub output: OutputSettings,
+	pub branch: BranchSettings,
+	pub backlinks: BacklinksSettings,
+}
+
+impl Config {
+	// Every setting that has a default, as used when nothing overrides it
+	pub fn builtin() -> Self {
+		let scraper = ScraperConfig::default();
+		let http = HttpConfig::default();
+
+		Config {
+			scrape: ScrapeSettings {
+				start: Some(scraper.start),
+				end: scraper.end,
+				limit: Some(scraper.limit),
+				max_age: Some(scraper.max_age.as_secs() / 60 / 60),
+				components: None,
+				extractors: None,
+				source: Some(scraper.use_source),
+				include: Some(scraper.include),
+			},
+			network: NetworkSettings {
+				retries: Some(http.retry.max_attempts - 1),
+				retry_time: Some(http.retry.max_total_time.as_secs()),
+				user_agent: Some(http.user_agent),
+				contact: None,
+				rate: Some(http.requests_per_second),
+				burst: Some(http.burst),
+				timeout: Some(http.timeout.as_secs()),
+				connect_timeout: Some(http.connect_timeout.as_secs()),
+				cache_dir: Some(PathBuf::from("cache")),
+			},
+			output: OutputSettings {
+				dir: Some(PathBuf::from("output")),
+				names: Some(Artifact::Names.file_name().into()),
+				database: Some(Artifact::Database.file_name().into()),
+				backlinks: Some(Artifact::Backlinks.file_name().into()),
+				metadata: Some(Artifact::PageMetadata.file_name().into()),
+			},
+			branch: BranchSettings {
+				name: Some(scraper.branch.name),
+				site_url: Some(scraper.branch.site_url),
+				series_urls: None,
+				number_format: Some(scraper.branch.number_format),
+				keywords: Some(scraper.branch.keywords),
+				classes: None,
+			},
+			backlinks: BacklinksSettings {
+				components: Some(default_backlink_components()),
+				depth: Some(scraper.backlink_depth),
+			},
+		}
+	}
+
+	pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+		let path = path.as_ref();
+		let contents = fs::read_to_string(path)
+			.with_context(|| format!("Failed to read config file {}", path.display()))?;
+		toml::from_str(&contents)
+			.with_context(|| format!("Invalid config file {}", path.display()))
+	}
+
+	pub fn overlay(&mut self, other: Config) {
+		self.scrape.overlay(other.scrape);
+		self.network.overlay(other.network);
+		self.output.overlay(other.output);
+		self.branch.overlay(other.branch);
+		self.backlinks.overlay(other.backlinks);
+	}
+
+	pub fn to_toml(&self) -> Result<String> {
+		Ok(toml::to_string_pretty(self)?)
+	}
+
+	// The scrape settings, without the flags that only apply to a single run
+	pub fn scraper_config(&self) -> ScraperConfig {
+		let default = ScraperConfig::default();
+		let scrape = &self.scrape;
+
+		ScraperConfig {
+			start: scrape.start.unwrap_or(default.start),
+			end: scrape.end.or(default.end),
+			limit: scrape.limit.unwrap_or(default.limit),
+			max_age: scrape
+				.max_age
+				.map(|hours| Duration::from_secs(hours * 60 * 60))
+				.unwrap_or(default.max_age),
+			backlink_components: self
+				.backlinks
+				.components
+				.clone()
+				.unwrap_or(default.backlink_components),
+			backlink_depth: self.backlinks.depth.unwrap_or(default.backlink_depth),
+			use_source: scrape.source.unwrap_or(default.use_source),
+			branch: self.branch(),
+			include: scrape.include.clone().unwrap_or(default.include),
+			..default
+		}
+	}
+
+	pub fn branch(&self) -> Branch {
+		let default = Branch::default();
+		let branch = &self.branch;
+
+		Branch {
+			name: branch.name.clone().unwrap_or(default.name),
+			site_url: branch.site_url.clone().unwrap_or(default.site_url),
+			series_urls: branch.series_urls.clone().unwrap_or(default.series_urls),
+			number_format: branch
+				.number_format
+				.clone()
+				.unwrap_or(default.number_format),
+			keywords: branch.keywords.clone().unwrap_or(default.keywords),
+			classes: branch.classes.clone().unwrap_or(default.classes),
+		}
+	}
+
+	pub fn http_config(&self) -> HttpConfig {
+		let default = HttpConfig::default();
+		let network = &self.network;
+
+		HttpConfig {
+			user_agent: network.user_agent.clone().unwrap_or(default.user_agent),
+			contact: network.contact.clone(),
+			requests_per_second: network.rate.unwrap_or(default.requests_per_second),
+			burst: network.burst.unwrap_or(default.burst),
+			timeout: network
+				.timeout
+				.map(Duration::from_secs)
+				.unwrap_or(default.timeout),
+			connect_timeout: network
+				.connect_timeout
+				.map(Duration::from_secs)
+				.unwrap_or(default.connect_timeout),
+			retry: RetryPolicy {
+				max_attempts: network
+					.retries
+					.map(|retries| retries + 1)
+					.unwrap_or(default.retry.max_attempts),
+				max_total_time: network
+					.retry_time
+					.map(Duration::from_secs)
+					.unwrap_or(default.retry.max_total_time),
+				..default.retry
+			},
+			..default
+		}
+	}
+
+	pub fn cache_dir(&self) -> PathBuf {
+		self.network
+			.cache_dir
+			.clone()
+			.unwrap_or_else(|| PathBuf::from("cache"))
+	}
+
+	pub fn sink(&self) -> JsonFileSink {
+		let output = &self.output;
+		let mut sink = match &output.dir {
+			Some(dir) => JsonFileSink::new(dir),
+			None => JsonFileSink::default(),
+		};
+		for (artifact, name) in [
+			(Artifact::Names, &output.names),
+			(Artifact::Database, &output.database),
+			(Artifact::Backlinks, &output.backlinks),
+			(Artifact::PageMetadata, &output.metadata),
+		] {
+			if let Some(name) = name {
+				sink = sink.with_file_name(artifact, name);
+			}
+		}
+		sink
+	}
+
+	// The configured component definitions, narrowed down to the enabled extractors
+	pub fn extractors(&self) -> Result<ExtractorRegistry> {
+		let components = match &self.scrape.components {
+			Some(path) => ComponentSet::load(path)?,
+			None => ComponentSet::builtin(),
+		};
+		let mut registry = ExtractorRegistry::from_components_with_keywords(
+			&components,
+			&self.branch().keywords,
+		)?;
+		if let Some(enabled) = &self.scrape.extractors {
+			registry.retain(enabled)?;
+		}
+		Ok(registry)
+	}
+}
+
+#[test]
+fn test_config_layers() {
+	let file: Config = toml::from_str(
+		r#"
+		[scrape]
+		start = 100
+		limit = 4
+		extractors = ["ACS Bar", "Backup"]
+		include = ["joke", "explained"]
+
+		[network]
+		rate = 0.5
+		burst = 4
+
+		[output]
+		dir = "runs/eu"
+
+		[branch]
+		name = "fr"
+		number_format = "SCP-{}-FR"
+
+		[branch.classes]
+		euclide = "euclid"
+
+		[[backlinks.components]]
+		slug = "component:anomaly-class-bar-source"
+		name = "ACS Bar"
+		"#,
+	)
+	.unwrap();
+	assert_eq!(file.scrape.end, None);
+
+	let env = Config {
+		scrape: ScrapeSettings {
+			limit: Some(8),
+			..Default::default()
+		},
+		..Default::default()
+	};
+	let cli = Config {
+		scrape: ScrapeSettings {
+			limit: Some(2),
+			..Default::default()
+		},
+		..Default::default()
+	};
+
+	let mut config = Config::builtin();
+	assert!(config
+		.scraper_config()
+		.backlink_components
+		.iter()
+		.any(|component| component.name == "ACS Lite Bar"));
+	config.overlay(file);
+	config.overlay(env);
+	config.overlay(cli);
+
+	let scraper = config.scraper_config();
+	assert_eq!((scraper.start, scraper.end, scraper.limit), (100, None, 2));
+	assert_eq!(scraper.backlink_components.len(), 1);
+	assert_eq!(
+		scraper.include,
+		vec![SeriesKind::Joke, SeriesKind::Explained]
+	);
+	assert_eq!(scraper.branch.format_number(1), "SCP-001-FR");
+	assert_eq!(scraper.branch.translate("Euclide"), "euclid");
+	assert_eq!(scraper.branch.site_url, Branch::default().site_url);
+	assert_eq!(config.http_config().requests_per_second, 0.5);
+	assert_eq!(config.http_config().burst, 4);
+	assert_eq!(
+		config.http_config().connect_timeout,
+		HttpConfig::default().connect_timeout
+	);
+	assert_eq!(
+		config.sink().path(Artifact::Database),
+		Path::new("runs/eu").join("acs_database.json")
+	);
+	assert_eq!(
+		config.extractors().unwrap().names(),
+		vec!["ACS Bar", "Backup"]
+	);
+
+	let shown: Config = toml::from_str(&config.to_toml().unwrap()).unwrap();
+	assert_eq!(shown, config);
+
+	assert!(toml::from_str::<Config>("[scrape]\nstrat = 1").is_err());
+}
@@ -0,0 +1,185 @@
+use anyhow::{anyhow, Error};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+// The suffixes of the SCPs outside the numbered series
+pub const SUFFIXES: [&str; 3] = ["J", "EX", "ARC"];
+
+// Besides SUFFIXES, the codes of the branches: SCP-001-FR, or SCP-CN-001 for the
+// branches that put theirs in front
+static DESIGNATION_RGX: Lazy<Regex> = Lazy::new(|| {
+	Regex::new(r"(?i)\bSCP-(?:([A-Z]{2,3})-)?(\d{1,4})(?:-([A-Z]{1,3}))?\b").unwrap()
+});
+
+// An SCP number with the suffix of its series or branch, e.g. SCP-173, SCP-1234-J,
+// SCP-8900-EX or SCP-001-FR
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SCPDesignation {
+	pub number: u16,
+	// Uppercase branch code written before the number
+	pub prefix: Option<String>,
+	// Uppercase, one of SUFFIXES or a branch code
+	pub suffix: Option<String>,
+}
+
+impl SCPDesignation {
+	pub fn new(number: u16) -> Self {
+		SCPDesignation {
+			number,
+			prefix: None,
+			suffix: None,
+		}
+	}
+
+	pub fn with_suffix(number: u16, suffix: &str) -> Self {
+		SCPDesignation {
+			suffix: Some(suffix.to_uppercase()),
+			..SCPDesignation::new(number)
+		}
+	}
+
+	pub fn with_prefix(number: u16, prefix: &str) -> Self {
+		SCPDesignation {
+			prefix: Some(prefix.to_uppercase()),
+			..SCPDesignation::new(number)
+		}
+	}
+
+	// The first designation in a piece of text or a URL, in any case
+	pub fn find(text: &str) -> Option<Self> {
+		let cap = DESIGNATION_RGX.captures(text)?;
+		Some(SCPDesignation {
+			number: cap[2].parse().ok()?,
+			prefix: cap.get(1).map(|prefix| prefix.as_str().to_uppercase()),
+			suffix: cap.get(3).map(|suffix| suffix.as_str().to_uppercase()),
+		})
+	}
+
+	pub fn prefix(&self) -> Option<&str> {
+		self.prefix.as_deref()
+	}
+
+	pub fn suffix(&self) -> Option<&str> {
+		self.suffix.as_deref()
+	}
+}
+
+// Numbers below 100 are written with three digits
+impl fmt::Display for SCPDesignation {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "SCP-")?;
+		if let Some(prefix) = &self.prefix {
+			write!(f, "{}-", prefix)?;
+		}
+		write!(f, "{:03}", self.number)?;
+		if let Some(suffix) = &self.suffix {
+			write!(f, "-{}", suffix)?;
+		}
+		Ok(())
+	}
+}
+
+// Only accepts a whole designation, unlike find
+impl FromStr for SCPDesignation {
+	type Err = Error;
+
+	fn from_str(text: &str) -> Result<Self, Self::Err> {
+		SCPDesignation::find(text.trim())
+			.filter(|designation| {
+				designation.to_string().eq_ignore_ascii_case(text.trim())
+			})
+			.ok_or_else(|| anyhow!("Invalid SCP designation: {}", text))
+	}
+}
+
+// The hub pages of the SCPs outside the numbered series
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SeriesKind {
+	// SCP-XXXX-J
+	Joke,
+	// SCP-XXXX-EX
+	Explained,
+	// SCP-XXXX-ARC
+	Archived,
+}
+
+impl SeriesKind {
+	pub fn hub_slug(&self) -> &'static str {
+		match self {
+			SeriesKind::Joke => "joke-scps",
+			SeriesKind::Explained => "scp-ex",
+			SeriesKind::Archived => "archived-scps",
+		}
+	}
+
+	pub fn hub_url(&self, site_url: &str) -> String {
+		format!("{}/{}", site_url.trim_end_matches('/'), self.hub_slug())
+	}
+
+	pub fn suffix(&self) -> &'static str {
+		match self {
+			SeriesKind::Joke => "J",
+			SeriesKind::Explained => "EX",
+			SeriesKind::Archived => "ARC",
+		}
+	}
+}
+
+// Accepts the name of the series or its suffix
+impl FromStr for SeriesKind {
+	type Err = Error;
+
+	fn from_str(text: &str) -> Result<Self, Self::Err> {
+		match text.trim().to_lowercase().as_str() {
+			"joke" | "j" => Ok(SeriesKind::Joke),
+			"explained" | "ex" => Ok(SeriesKind::Explained),
+			"archived" | "arc" => Ok(SeriesKind::Archived),
+			_ => Err(anyhow!(
+				"'{}' is not one of joke, explained or archived",
+				text
+			)),
+		}
+	}
+}
+
+#[test]
+fn test_scp_designation() {
+	assert_eq!(SCPDesignation::new(2).to_string(), "SCP-002");
+	assert_eq!(SCPDesignation::new(173).to_string(), "SCP-173");
+	assert_eq!(
+		SCPDesignation::with_suffix(1234, "j").to_string(),
+		"SCP-1234-J"
+	);
+
+	assert_eq!(
+		SCPDesignation::find("https://scp-wiki.wikidot.com/scp-8900-ex"),
+		Some(SCPDesignation::with_suffix(8900, "EX"))
+	);
+	assert_eq!(
+		SCPDesignation::find("SCP-173 - The Sculpture"),
+		Some(SCPDesignation::new(173))
+	);
+	assert_eq!(
+		SCPDesignation::find("/scp-1234-jeff"),
+		Some(SCPDesignation::new(1234)),
+		"A suffix has to end at a word boundary"
+	);
+
+	assert_eq!(
+		"scp-049-arc".parse::<SCPDesignation>().unwrap(),
+		SCPDesignation::with_suffix(49, "ARC")
+	);
+	assert!("SCP-173 - The Sculpture".parse::<SCPDesignation>().is_err());
+	assert_eq!(
+		"SCP-001-fr".parse::<SCPDesignation>().unwrap(),
+		SCPDesignation::with_suffix(1, "FR")
+	);
+	assert_eq!(
+		SCPDesignation::find("http://scp-wiki-cn.wikidot.com/scp-cn-1000"),
+		Some(SCPDesignation::with_prefix(1000, "CN"))
+	);
+	assert!("SCP-1234-FRENCH".parse::<SCPDesignation>().is_err());
+}
@@ -0,0 +1,157 @@
+use serde::Serialize;
+use std::{collections::BTreeMap, fmt, sync::Mutex};
+use thiserror::Error;
+
+// Why a page could not be turned into an ACS entry
+#[derive(Debug, Error)]
+pub enum ScrapeError {
+	#[error("Page not found: {url}")]
+	NotFound { url: String },
+
+	#[error("No ACS found on {url}")]
+	NoAcs { url: String },
+
+	#[error("Request to {url} timed out")]
+	Timeout { url: String },
+
+	#[error("Request to {url} failed: {source:#}")]
+	Network {
+		url: String,
+		#[source]
+		source: anyhow::Error,
+	},
+
+	#[error("Failed to fetch {url} - Status: {status}")]
+	Status { url: String, status: u16 },
+
+	#[error("Not in cache: {url}")]
+	NotCached { url: String },
+
+	#[error("{actual_number} is not in scp_names.json")]
+	NameNotFound { actual_number: String },
+
+	#[error("Failed to parse {url}: {message}")]
+	Parse { url: String, message: String },
+
+	#[error(transparent)]
+	Other(#[from] anyhow::Error),
+}
+
+impl ScrapeError {
+	// Sorts a failed request into a timeout or any other network failure
+	pub fn from_request(url: &str, error: anyhow::Error) -> Self {
+		let timed_out = error
+			.chain()
+			.filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+			.any(|cause| cause.is_timeout());
+
+		if timed_out {
+			ScrapeError::Timeout {
+				url: url.to_string(),
+			}
+		} else {
+			ScrapeError::Network {
+				url: url.to_string(),
+				source: error,
+			}
+		}
+	}
+
+	pub fn kind(&self) -> ErrorKind {
+		match self {
+			ScrapeError::NotFound { .. } => ErrorKind::NotFound,
+			ScrapeError::NoAcs { .. } => ErrorKind::NoAcs,
+			ScrapeError::Timeout { .. } => ErrorKind::Timeout,
+			ScrapeError::Network { .. } => ErrorKind::Network,
+			ScrapeError::Status { .. } => ErrorKind::Status,
+			ScrapeError::NotCached { .. } => ErrorKind::NotCached,
+			ScrapeError::NameNotFound { .. } => ErrorKind::NameNotFound,
+			ScrapeError::Parse { .. } => ErrorKind::Parse,
+			ScrapeError::Other(_) => ErrorKind::Other,
+		}
+	}
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+	NotFound,
+	NoAcs,
+	Timeout,
+	Network,
+	Status,
+	NotCached,
+	NameNotFound,
+	Parse,
+	Other,
+}
+
+impl fmt::Display for ErrorKind {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let name = match self {
+			ErrorKind::NotFound => "Page not found",
+			ErrorKind::NoAcs => "No ACS found",
+			ErrorKind::Timeout => "Timed out",
+			ErrorKind::Network => "Network error",
+			ErrorKind::Status => "Unexpected status",
+			ErrorKind::NotCached => "Not in cache",
+			ErrorKind::NameNotFound => "Name not in scp_names.json",
+			ErrorKind::Parse => "Parse failure",
+			ErrorKind::Other => "Other",
+		};
+		write!(f, "{}", name)
+	}
+}
+
+// Every failure of a run, grouped by kind
+#[derive(Debug, Default)]
+pub struct ErrorReport {
+	errors: Mutex<BTreeMap<ErrorKind, Vec<String>>>,
+}
+
+impl ErrorReport {
+	pub fn record(&self, error: &ScrapeError) {
+		let mut errors = self.errors.lock().unwrap();
+		errors
+			.entry(error.kind())
+			.or_default()
+			.push(error.to_string());
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.errors.lock().unwrap().is_empty()
+	}
+
+	pub fn grouped(&self) -> BTreeMap<ErrorKind, Vec<String>> {
+		self.errors.lock().unwrap().clone()
+	}
+}
+
+#[test]
+fn test_error_report() {
+	let report = ErrorReport::default();
+	assert!(report.is_empty());
+
+	report.record(&ScrapeError::NotFound {
+		url: "https://scp-wiki.wikidot.com/scp-9999".to_string(),
+	});
+	report.record(&ScrapeError::NameNotFound {
+		actual_number: "SCP-9998".to_string(),
+	});
+	report.record(&ScrapeError::NotFound {
+		url: "https://scp-wiki.wikidot.com/scp-9997".to_string(),
+	});
+
+	let grouped = report.grouped();
+	assert_eq!(grouped[&ErrorKind::NotFound].len(), 2);
+	assert_eq!(
+		grouped[&ErrorKind::NameNotFound],
+		vec!["SCP-9998 is not in scp_names.json"]
+	);
+
+	let network = ScrapeError::from_request(
+		"https://scp-wiki.wikidot.com/scp-173",
+		anyhow::anyhow!("connection reset"),
+	);
+	assert_eq!(network.kind(), ErrorKind::Network);
+}
@@ -0,0 +1,373 @@
+use crate::acs::{Acs, SharedAcs};
+use crate::branch::Keywords;
+use crate::components::{ComponentExtractor, ComponentSet};
+use crate::instances::{into_instances, AcsInstance, FoundInstance};
+use crate::page::{Location, PageStructure, Region};
+use crate::text::{clean_text, extract_string_after_colon};
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use scraper::Html;
+use std::collections::{BTreeMap, HashMap};
+
+static BUILTIN_EXTRACTORS: Lazy<Vec<ComponentExtractor>> = Lazy::new(|| {
+	ComponentSet::builtin()
+		.extractors()
+		.expect("Built-in component selectors are invalid")
+});
+
+fn builtin(name: &str) -> &'static ComponentExtractor {
+	BUILTIN_EXTRACTORS
+		.iter()
+		.find(|e| e.name() == name)
+		.unwrap_or_else(|| panic!("No built-in component named {}", name))
+}
+
+macro_rules! get_clean {
+	($item:expr, $results:expr) => {
+		$results
+			.get($item)
+			.map(|s| clean_text(s))
+			.unwrap_or_else(String::new)
+	};
+}
+
+// Text Strings scraping if ACS Bar is not found.
+// Searches the pages for specific phrases/words and adds them to the database if found.
+// Only the main content and the tabs are searched, collapsibles, footnotes and the site
+// chrome hold addenda, asides and boilerplate like "Cite this page"
+
+pub fn backup_acs_function(document: &Html) -> Option<Acs> {
+	backup_acs_with_keywords(document, &Keywords::default())
+}
+
+// The Text Strings search with the labels of a branch's language
+pub fn backup_acs_with_keywords(document: &Html, keywords: &Keywords) -> Option<Acs> {
+	let page = PageStructure::parse(document);
+	let sections: Vec<(&Location, String)> = page
+		.sections(Region::Main)
+		.chain(page.sections(Region::Tab))
+		.map(|section| (&section.location, section.text.to_lowercase()))
+		.collect();
+
+	let mut results = HashMap::new();
+	let mut regions = BTreeMap::new();
+
+	let labels = [
+		(&keywords.containment, "contain"),
+		(&keywords.disruption, "disrupt"),
+		(&keywords.risk, "risk"),
+		(&keywords.secondary, "secondary"),
+	];
+
+	for (search_strs, result_key) in labels {
+		let found = search_strs.iter().find_map(|search_str| {
+			let search_str = search_str.to_lowercase();
+			sections.iter().find_map(|(location, text)| {
+				text.find(&search_str)
+					.map(|index| (search_str.clone(), *location, &text[index..]))
+			})
+		});
+		if let Some((search_str, location, text)) = found {
+			let result_value = extract_string_after_colon(text);
+			results.insert(result_key.to_string(), result_value);
+			regions.insert(result_key.to_string(), location.clone());
+
+			log::info!(
+				"Backup Function found {} | {} in {:?}",
+				search_str,
+				result_key,
+				location
+			);
+		}
+	}
+
+	for keyword in &keywords.disruption_classes {
+		let keyword = format!(" {} ", keyword.trim().to_lowercase());
+		let found = sections.iter().find(|(_, text)| text.contains(&keyword));
+		if let Some((location, _)) = found {
+			results.insert("disrupt".to_string(), keyword.trim().to_string());
+			regions.insert("disrupt".to_string(), (*location).clone());
+			log::info!(
+				"Backup Function found keyword: {} in {:?}",
+				keyword,
+				location
+			);
+			break;
+		}
+	}
+
+	let contain = get_clean!("contain", results);
+	let disrupt = get_clean!("disrupt", results);
+	let risk = get_clean!("risk", results);
+	let secondary = get_clean!("secondary", results);
+
+	if disrupt.is_empty() && risk.is_empty() && secondary.is_empty() {
+		return None;
+	}
+
+	let acs_backup = Acs::Backup {
+		shared: SharedAcs {
+			contain,
+			secondary,
+			disrupt,
+			scraper: "Backup".to_string(),
+		},
+		risk,
+		regions,
+	};
+
+	log::info!("Final Results for Page\n{:?}", acs_backup);
+
+	Some(acs_backup)
+}
+
+// ACS Bar Scraper
+pub fn get_acs_bar(document: &Html) -> Acs {
+	builtin("ACS Bar").extract_fields(document)
+}
+
+// ACS Hybrid Bar Scraper
+pub fn get_acs_hybrid_bar(document: &Html) -> Acs {
+	builtin("ACS Hybrid Bar").extract_fields(document)
+}
+
+// Flops Header Scraper
+pub fn get_flops_header(document: &Html) -> Acs {
+	builtin("Flops Header").extract_fields(document)
+}
+
+// AIM Header Scraper
+pub fn get_aim_header(document: &Html) -> Acs {
+	builtin("AIM Header").extract_fields(document)
+}
+
+// An ACS-style component that can be found on a page and scraped into an Acs entry
+pub trait AcsExtractor: Send + Sync {
+	// Shown in logs and stored in the entry's `scraper` field
+	fn name(&self) -> &str;
+
+	// Extractors with a higher priority are tried first
+	fn priority(&self) -> i32;
+
+	fn detect(&self, document: &Html) -> bool;
+
+	fn extract(&self, document: &Html) -> Option<Acs>;
+
+	// Every instance of the component on the page. Extractors that can't tell instances
+	// apart return their single result
+	fn extract_all(&self, document: &Html) -> Vec<FoundInstance> {
+		self.extract(document)
+			.map(|acs| FoundInstance::new(acs, document, None))
+			.into_iter()
+			.collect()
+	}
+
+	// Fallbacks only add an instance when no component was found on the page
+	fn is_fallback(&self) -> bool {
+		false
+	}
+}
+
+// Text Strings scraping, tried on every page that no component was found on
+#[derive(Default)]
+pub struct BackupExtractor {
+	pub keywords: Keywords,
+}
+
+impl AcsExtractor for BackupExtractor {
+	fn name(&self) -> &str {
+		"Backup"
+	}
+
+	fn priority(&self) -> i32 {
+		i32::MIN
+	}
+
+	fn detect(&self, _document: &Html) -> bool {
+		true
+	}
+
+	fn extract(&self, document: &Html) -> Option<Acs> {
+		backup_acs_with_keywords(document, &self.keywords)
+	}
+
+	fn is_fallback(&self) -> bool {
+		true
+	}
+}
+
+// The set of extractors a page is run through, ordered by priority
+pub struct ExtractorRegistry {
+	extractors: Vec<Box<dyn AcsExtractor>>,
+}
+
+impl ExtractorRegistry {
+	pub fn empty() -> Self {
+		ExtractorRegistry {
+			extractors: Vec::new(),
+		}
+	}
+
+	// Extractors with equal priority keep their registration order
+	pub fn register(&mut self, extractor: impl AcsExtractor + 'static) -> &mut Self {
+		let index = self
+			.extractors
+			.iter()
+			.position(|existing| existing.priority() < extractor.priority())
+			.unwrap_or(self.extractors.len());
+		self.extractors.insert(index, Box::new(extractor));
+		self
+	}
+
+	// The given components plus the Text Strings backup
+	pub fn from_components(components: &ComponentSet) -> Result<Self> {
+		ExtractorRegistry::from_components_with_keywords(components, &Keywords::default())
+	}
+
+	// The given components plus the Text Strings backup looking for a branch's labels
+	pub fn from_components_with_keywords(
+		components: &ComponentSet,
+		keywords: &Keywords,
+	) -> Result<Self> {
+		let mut registry = ExtractorRegistry::empty();
+		for extractor in components.extractors()? {
+			registry.register(extractor);
+		}
+		registry.register(BackupExtractor {
+			keywords: keywords.clone(),
+		});
+		Ok(registry)
+	}
+
+	// Keeps only the named extractors, failing on a name none of them has
+	pub fn retain(&mut self, names: &[String]) -> Result<()> {
+		let enabled = |extractor: &str| {
+			names
+				.iter()
+				.any(|name| name.eq_ignore_ascii_case(extractor))
+		};
+		if let Some(unknown) = names.iter().find(|name| {
+			!self
+				.extractors
+				.iter()
+				.any(|e| e.name().eq_ignore_ascii_case(name))
+		}) {
+			return Err(anyhow!(
+				"Unknown extractor '{}', expected one of: {}",
+				unknown,
+				self.names().join(", ")
+			));
+		}
+
+		self.extractors.retain(|e| enabled(e.name()));
+		Ok(())
+	}
+
+	pub fn names(&self) -> Vec<&str> {
+		self.extractors.iter().map(|e| e.name()).collect()
+	}
+
+	// Runs the first detected extractor that yields data
+	pub fn extract(&self, document: &Html) -> Option<Acs> {
+		for extractor in &self.extractors {
+			if !extractor.detect(document) {
+				continue;
+			}
+
+			log::debug!("Detected {}", extractor.name());
+			if let Some(acs) = extractor.extract(document) {
+				return Some(acs);
+			}
+		}
+
+		None
+	}
+
+	// Every instance found by the detected extractors, in page order. The primary instance
+	// is the one `extract` returns
+	pub fn extract_instances(&self, document: &Html) -> Vec<AcsInstance> {
+		let mut found: Vec<FoundInstance> = Vec::new();
+		let mut primary = None;
+
+		for extractor in &self.extractors {
+			if extractor.is_fallback() && !found.is_empty() {
+				continue;
+			}
+			if !extractor.detect(document) {
+				continue;
+			}
+
+			let instances = extractor.extract_all(document);
+			if primary.is_none() && !instances.is_empty() {
+				primary = Some(found.len());
+			}
+			found.extend(instances);
+		}
+
+		into_instances(found, primary.unwrap_or(0))
+	}
+}
+
+impl Default for ExtractorRegistry {
+	fn default() -> Self {
+		ExtractorRegistry::from_components(&ComponentSet::builtin())
+			.expect("Built-in component selectors are invalid")
+	}
+}
+
+#[test]
+fn test_extractor_registry_priority() {
+	struct CustomHeader;
+
+	impl AcsExtractor for CustomHeader {
+		fn name(&self) -> &str {
+			"Custom Header"
+		}
+
+		fn priority(&self) -> i32 {
+			100
+		}
+
+		fn detect(&self, document: &Html) -> bool {
+			document
+				.select(&scraper::Selector::parse("div.custom-header").unwrap())
+				.next()
+				.is_some()
+		}
+
+		fn extract(&self, _document: &Html) -> Option<Acs> {
+			Some(Acs::Backup {
+				shared: SharedAcs {
+					contain: "keter".to_string(),
+					secondary: String::new(),
+					disrupt: String::new(),
+					scraper: self.name().to_string(),
+				},
+				risk: String::new(),
+				regions: BTreeMap::new(),
+			})
+		}
+	}
+
+	let mut registry = ExtractorRegistry::default();
+	registry.register(CustomHeader);
+	assert_eq!(registry.names().first(), Some(&"Custom Header"));
+	assert_eq!(registry.names().last(), Some(&"Backup"));
+
+	let document = Html::parse_document(
+		r#"<div class="custom-header"></div><div class="anom-bar-container"></div>"#,
+	);
+	match registry.extract(&document) {
+		Some(Acs::Backup { shared, .. }) => assert_eq!(shared.scraper, "Custom Header"),
+		other => panic!("Expected the custom extractor to win, got {:?}", other),
+	}
+
+	let document = Html::parse_document("<p>Nothing to see here</p>");
+	assert!(registry.extract(&document).is_none());
+
+	registry
+		.retain(&["backup".to_string(), "ACS Bar".to_string()])
+		.unwrap();
+	assert_eq!(registry.names(), vec!["ACS Bar", "Backup"]);
+	assert!(registry.retain(&["Nope".to_string()]).is_err());
+}
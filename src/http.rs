@@ -0,0 +1,399 @@
+use crate::retry::{AttemptLog, RetryPolicy};
+use anyhow::Result;
+use reqwest::{header::HeaderMap, Client, RequestBuilder, Response, StatusCode};
+use serde::Serialize;
+use std::{
+	collections::HashMap,
+	sync::{Arc, Mutex},
+	time::{Duration, Instant, SystemTime},
+};
+
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+	pub user_agent: String,
+	// Appended to the user agent so the wiki admins know who to reach, e.g. an email
+	pub contact: Option<String>,
+	// Requests per second allowed to a single host, 0 disables the limit
+	pub requests_per_second: f64,
+	// How many requests may be sent back to back before the rate applies
+	pub burst: u32,
+	pub timeout: Duration,
+	pub connect_timeout: Duration,
+	pub pool_max_idle_per_host: usize,
+	pub retry: RetryPolicy,
+	// Upper bound for a single Retry-After wait
+	pub max_retry_after: Duration,
+}
+
+impl Default for HttpConfig {
+	fn default() -> Self {
+		HttpConfig {
+			user_agent: format!("acs_database_rs/{}", env!("CARGO_PKG_VERSION")),
+			contact: None,
+			requests_per_second: 2.0,
+			burst: 2,
+			timeout: Duration::from_secs(30),
+			connect_timeout: Duration::from_secs(10),
+			pool_max_idle_per_host: 10,
+			retry: RetryPolicy::default(),
+			max_retry_after: Duration::from_secs(120),
+		}
+	}
+}
+
+impl HttpConfig {
+	pub fn full_user_agent(&self) -> String {
+		match &self.contact {
+			Some(contact) => format!("{} ({})", self.user_agent, contact),
+			None => self.user_agent.clone(),
+		}
+	}
+}
+
+// A response with its body already read, so a connection dropped halfway through the
+// body is retried like any other failed request
+#[derive(Debug)]
+pub struct HttpResponse {
+	pub status: StatusCode,
+	pub headers: HeaderMap,
+	pub body: String,
+}
+
+impl HttpResponse {
+	async fn read(response: Response) -> reqwest::Result<Self> {
+		let status = response.status();
+		let headers = response.headers().clone();
+		let body = response.text().await?;
+		Ok(HttpResponse {
+			status,
+			headers,
+			body,
+		})
+	}
+}
+
+struct BucketState {
+	tokens: f64,
+	last_refill: Instant,
+	blocked_until: Option<Instant>,
+}
+
+// Token bucket for a single host. Also holds back every request to the host while the
+// server has asked us to wait
+struct TokenBucket {
+	rate: f64,
+	capacity: f64,
+	state: Mutex<BucketState>,
+}
+
+impl TokenBucket {
+	fn new(rate: f64, burst: u32) -> Self {
+		let capacity = f64::from(burst.max(1));
+		TokenBucket {
+			rate,
+			capacity,
+			state: Mutex::new(BucketState {
+				tokens: capacity,
+				last_refill: Instant::now(),
+				blocked_until: None,
+			}),
+		}
+	}
+
+	// Takes a token if one is available, otherwise returns how long to wait
+	fn try_acquire(&self) -> Option<Duration> {
+		let mut state = self.state.lock().unwrap();
+		let now = Instant::now();
+
+		if let Some(until) = state.blocked_until {
+			if until > now {
+				return Some(until - now);
+			}
+			state.blocked_until = None;
+		}
+
+		if self.rate <= 0.0 {
+			return None;
+		}
+
+		let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+		state.tokens = (state.tokens + elapsed * self.rate).min(self.capacity);
+		state.last_refill = now;
+
+		if state.tokens >= 1.0 {
+			state.tokens -= 1.0;
+			None
+		} else {
+			Some(Duration::from_secs_f64((1.0 - state.tokens) / self.rate))
+		}
+	}
+
+	async fn acquire(&self) {
+		while let Some(wait) = self.try_acquire() {
+			tokio::time::sleep(wait).await;
+		}
+	}
+
+	fn block_for(&self, wait: Duration) {
+		let mut state = self.state.lock().unwrap();
+		let until = Instant::now() + wait;
+		if state.blocked_until.is_none_or(|current| current < until) {
+			state.blocked_until = Some(until);
+		}
+	}
+}
+
+// The one HTTP client every subsystem goes through: pooled connections, a shared user
+// agent, per host rate limiting, retries and Retry-After handling
+pub struct HttpClient {
+	client: Client,
+	config: HttpConfig,
+	buckets: Mutex<HashMap<String, Arc<TokenBucket>>>,
+	attempts: AttemptLog,
+}
+
+impl HttpClient {
+	pub fn new(config: HttpConfig) -> Result<Self> {
+		let client = Client::builder()
+			.user_agent(config.full_user_agent())
+			.timeout(config.timeout)
+			.connect_timeout(config.connect_timeout)
+			.pool_max_idle_per_host(config.pool_max_idle_per_host)
+			.build()?;
+
+		Ok(HttpClient {
+			client,
+			config,
+			buckets: Mutex::new(HashMap::new()),
+			attempts: AttemptLog::default(),
+		})
+	}
+
+	pub fn config(&self) -> &HttpConfig {
+		&self.config
+	}
+
+	pub fn inner(&self) -> &Client {
+		&self.client
+	}
+
+	// Attempts spent on every URL requested so far
+	pub fn attempts(&self) -> &AttemptLog {
+		&self.attempts
+	}
+
+	fn bucket(&self, url: &str) -> Arc<TokenBucket> {
+		let host = reqwest::Url::parse(url)
+			.ok()
+			.and_then(|url| url.host_str().map(|host| host.to_string()))
+			.unwrap_or_default();
+
+		let mut buckets = self.buckets.lock().unwrap();
+		Arc::clone(buckets.entry(host).or_insert_with(|| {
+			Arc::new(TokenBucket::new(
+				self.config.requests_per_second,
+				self.config.burst,
+			))
+		}))
+	}
+
+	pub async fn get(&self, url: &str) -> Result<HttpResponse> {
+		self.send(url, || self.client.get(url)).await
+	}
+
+	pub async fn get_with_headers(
+		&self,
+		url: &str,
+		headers: &HeaderMap,
+	) -> Result<HttpResponse> {
+		self.send(url, || self.client.get(url).headers(headers.clone()))
+			.await
+	}
+
+	pub async fn post_form<T: Serialize + ?Sized>(
+		&self,
+		url: &str,
+		headers: &HeaderMap,
+		form: &T,
+	) -> Result<HttpResponse> {
+		self.send(url, || {
+			self.client.post(url).headers(headers.clone()).form(form)
+		})
+		.await
+	}
+
+	// Sends the request once the host's bucket allows it and retries according to the
+	// retry policy. A 429 or 503 with Retry-After pauses the whole host for that period.
+	// When the retries run out on a retryable status the last response is returned. The
+	// body is read as part of each attempt, so failing to read it is retried too.
+	async fn send(
+		&self,
+		url: &str,
+		request: impl Fn() -> RequestBuilder,
+	) -> Result<HttpResponse> {
+		let policy = &self.config.retry;
+		let bucket = self.bucket(url);
+		let started = Instant::now();
+		let mut attempt = 0;
+		let mut retried_for: Vec<String> = Vec::new();
+
+		loop {
+			attempt += 1;
+			bucket.acquire().await;
+
+			let error = match request().send().await {
+				Ok(response) => {
+					let status = response.status();
+					log::debug!("Received status {} from {}", status, url);
+
+					let suggested = retry_after(response.headers())
+						.map(|wait| wait.min(self.config.max_retry_after));
+					let delay = policy
+						.is_retryable_status(status)
+						.then(|| policy.next_delay(attempt, started, suggested));
+					match delay {
+						Some(Some(wait)) => {
+							log::warn!(
+								"{} from {}, retrying in {:?} (attempt {})",
+								status,
+								url,
+								wait,
+								attempt
+							);
+							retried_for.push(status.to_string());
+							if suggested.is_some()
+								|| status == StatusCode::TOO_MANY_REQUESTS
+								|| status == StatusCode::SERVICE_UNAVAILABLE
+							{
+								bucket.block_for(wait);
+							} else {
+								tokio::time::sleep(wait).await;
+							}
+							continue;
+						}
+						giving_up => {
+							if giving_up.is_some() {
+								log::warn!(
+									"Giving up on {} after {} attempts - Status: {}",
+									url,
+									attempt,
+									status
+								);
+							}
+							match HttpResponse::read(response).await {
+								Ok(response) => {
+									self.attempts.record(
+										url,
+										attempt,
+										retried_for,
+										&status.to_string(),
+									);
+									return Ok(response);
+								}
+								Err(error) => error,
+							}
+						}
+					}
+				}
+				Err(error) => error,
+			};
+
+			let wait = policy
+				.is_retryable_error(&error)
+				.then(|| policy.next_delay(attempt, started, None))
+				.flatten();
+			let Some(wait) = wait else {
+				self.attempts
+					.record(url, attempt, retried_for, error_reason(&error));
+				return Err(anyhow::Error::new(error).context(format!(
+					"Request to {} failed after {} attempts",
+					url, attempt
+				)));
+			};
+
+			log::warn!(
+				"Request to {} failed, retrying in {:?} (attempt {}): {}",
+				url,
+				wait,
+				attempt,
+				error
+			);
+			retried_for.push(error_reason(&error).to_string());
+			tokio::time::sleep(wait).await;
+		}
+	}
+}
+
+impl Default for HttpClient {
+	fn default() -> Self {
+		HttpClient::new(HttpConfig::default()).expect("Failed to build the HTTP client")
+	}
+}
+
+// A short description of a failed request for the run summary
+fn error_reason(error: &reqwest::Error) -> &'static str {
+	if error.is_timeout() {
+		"timed out"
+	} else if error.is_connect() {
+		"connection failed"
+	} else if error.is_body() || error.is_decode() {
+		"body failed"
+	} else {
+		"request failed"
+	}
+}
+
+// Reads a Retry-After header given either in seconds or as an HTTP date
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+	let value = headers
+		.get(reqwest::header::RETRY_AFTER)?
+		.to_str()
+		.ok()?
+		.trim();
+
+	if let Ok(seconds) = value.parse::<u64>() {
+		return Some(Duration::from_secs(seconds));
+	}
+
+	let date = httpdate::parse_http_date(value).ok()?;
+	Some(
+		date.duration_since(SystemTime::now())
+			.unwrap_or(Duration::ZERO),
+	)
+}
+
+#[test]
+fn test_retry_after() {
+	use reqwest::header::{HeaderValue, RETRY_AFTER};
+
+	let mut headers = HeaderMap::new();
+	assert_eq!(retry_after(&headers), None);
+
+	headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+	assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+
+	headers.insert(
+		RETRY_AFTER,
+		HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+	);
+	assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+
+	headers.insert(RETRY_AFTER, HeaderValue::from_static("soon"));
+	assert_eq!(retry_after(&headers), None);
+}
+
+#[test]
+fn test_token_bucket() {
+	let bucket = TokenBucket::new(1.0, 2);
+	assert_eq!(bucket.try_acquire(), None);
+	assert_eq!(bucket.try_acquire(), None);
+	assert!(bucket.try_acquire().is_some());
+
+	let unlimited = TokenBucket::new(0.0, 1);
+	for _ in 0..10 {
+		assert_eq!(unlimited.try_acquire(), None);
+	}
+
+	unlimited.block_for(Duration::from_secs(5));
+	assert!(unlimited.try_acquire().is_some());
+}
@@ -0,0 +1,151 @@
+use crate::cache::{unix_now, CachedPage};
+use crate::page;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+static PAGE_CONTENT_SELECTOR: Lazy<Selector> =
+	Lazy::new(|| Selector::parse("#page-content").unwrap());
+static PAGE_INFO_SELECTOR: Lazy<Selector> =
+	Lazy::new(|| Selector::parse("#page-info").unwrap());
+static ODATE_SELECTOR: Lazy<Selector> =
+	Lazy::new(|| Selector::parse("#page-info span.odate").unwrap());
+static REVISION_RGX: Lazy<Regex> =
+	Lazy::new(|| Regex::new(r"(?i)page revision:\s*(\d+)").unwrap());
+static ODATE_TIME_RGX: Lazy<Regex> = Lazy::new(|| Regex::new(r"time_(\d+)").unwrap());
+
+// What was known about a page the last time it was scraped
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PageMetadata {
+	pub url: String,
+	pub actual_number: String,
+	pub fetched_at: u64,
+	// sha256 of the page content, without the site chrome around it
+	pub content_hash: String,
+	// Wikidot's revision number and last edit time from the page footer
+	pub revision: Option<u32>,
+	pub last_edited: Option<u64>,
+}
+
+impl PageMetadata {
+	pub fn new(
+		url: &str,
+		actual_number: &str,
+		page: &CachedPage,
+		document: &Html,
+	) -> Self {
+		let page_info = document
+			.select(&PAGE_INFO_SELECTOR)
+			.next()
+			.map(|info| info.text().collect::<String>())
+			.unwrap_or_default();
+		let revision = REVISION_RGX
+			.captures(&page_info)
+			.and_then(|cap| cap[1].parse().ok());
+		let last_edited = document
+			.select(&ODATE_SELECTOR)
+			.next()
+			.and_then(|odate| odate.value().attr("class"))
+			.and_then(|class| ODATE_TIME_RGX.captures(class))
+			.and_then(|cap| cap[1].parse().ok());
+
+		PageMetadata {
+			url: url.to_string(),
+			actual_number: actual_number.to_string(),
+			fetched_at: page.fetched_at,
+			content_hash: content_hash(document),
+			revision,
+			last_edited,
+		}
+	}
+
+	pub fn is_older_than(&self, max_age: Duration) -> bool {
+		unix_now().saturating_sub(self.fetched_at) >= max_age.as_secs()
+	}
+
+	// Prefers the revision number, which only changes with an edit to the page
+	pub fn is_unchanged(&self, current: &PageMetadata) -> bool {
+		match (self.revision, current.revision) {
+			(Some(previous), Some(current)) => previous == current,
+			_ => self.content_hash == current.content_hash,
+		}
+	}
+}
+
+// Hashes the page content, falling back to the whole document when there is none. The
+// rating widget and the rest of the chrome inside the content change without an edit, so
+// they are left out
+pub fn content_hash(document: &Html) -> String {
+	let mut document = document.clone();
+	let chrome: Vec<_> = document
+		.tree
+		.nodes()
+		.filter(|node| node.value().as_element().is_some_and(page::is_chrome))
+		.map(|node| node.id())
+		.collect();
+	for id in chrome {
+		if let Some(mut node) = document.tree.get_mut(id) {
+			node.detach();
+		}
+	}
+
+	let content = document
+		.select(&PAGE_CONTENT_SELECTOR)
+		.next()
+		.map(|content| content.html())
+		.unwrap_or_else(|| document.html());
+
+	format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+#[test]
+fn test_page_metadata() {
+	let page = |body: &str| CachedPage {
+		request: "GET https://scp-wiki.wikidot.com/scp-173".to_string(),
+		status: 200,
+		headers: Default::default(),
+		body: body.to_string(),
+		fetched_at: unix_now(),
+	};
+	let html = |content: &str, revision: &str| {
+		format!(
+			r#"<div id="page-content">{}<div class="page-rate-widget-box"><span class="rate-points">rating: <span class="number">+9000</span></span></div></div>
+			<div id="page-info">page revision: {}, last edited: <span class="odate time_1700000000 format_%25e">14 Nov 2023</span></div>"#,
+			content, revision
+		)
+	};
+
+	let first_body = html("<p>Euclid</p>", "12");
+	let first = PageMetadata::new(
+		"https://scp-wiki.wikidot.com/scp-173",
+		"SCP-173",
+		&page(&first_body),
+		&Html::parse_document(&first_body),
+	);
+	assert_eq!(first.revision, Some(12));
+	assert_eq!(first.last_edited, Some(1700000000));
+	assert!(!first.is_older_than(Duration::from_secs(60)));
+	assert!(first.is_older_than(Duration::ZERO));
+
+	let rated_body = first_body.replace("+9000", "+9001");
+	let rated = PageMetadata::new(
+		"https://scp-wiki.wikidot.com/scp-173",
+		"SCP-173",
+		&page(&rated_body),
+		&Html::parse_document(&rated_body),
+	);
+	assert_eq!(first.content_hash, rated.content_hash);
+
+	let edited_body = html("<p>Keter</p>", "13");
+	let edited = PageMetadata::new(
+		"https://scp-wiki.wikidot.com/scp-173",
+		"SCP-173",
+		&page(&edited_body),
+		&Html::parse_document(&edited_body),
+	);
+	assert!(first.is_unchanged(&rated));
+	assert!(!first.is_unchanged(&edited));
+}
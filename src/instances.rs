@@ -0,0 +1,148 @@
+use crate::acs::Acs;
+use crate::page;
+use scraper::{ElementRef, Html};
+use serde::{Deserialize, Serialize};
+
+// Where on the page an ACS instance sits
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstanceContext {
+	// Position among the page's instances, starting at 0
+	pub order: usize,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub tab: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub collapsible: Option<String>,
+}
+
+// One of several ACS components found on a page. The primary one is the instance the
+// entry's own fields are taken from
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AcsInstance {
+	#[serde(flatten)]
+	pub context: InstanceContext,
+	#[serde(default)]
+	pub primary: bool,
+	#[serde(flatten)]
+	pub acs: Acs,
+}
+
+// An instance as found by an extractor, before the page's instances are put in order
+#[derive(Debug, Clone)]
+pub struct FoundInstance {
+	pub acs: Acs,
+	// Position of the component's element in the document
+	pub position: usize,
+	pub tab: Option<String>,
+	pub collapsible: Option<String>,
+}
+
+impl FoundInstance {
+	pub fn new(acs: Acs, document: &Html, element: Option<ElementRef>) -> Self {
+		match element {
+			Some(element) => FoundInstance {
+				acs,
+				position: position(document, element),
+				tab: tab_title(element),
+				collapsible: collapsible_label(element),
+			},
+			None => FoundInstance {
+				acs,
+				position: 0,
+				tab: None,
+				collapsible: None,
+			},
+		}
+	}
+}
+
+// Orders the found instances as they appear on the page and marks the primary one
+pub fn into_instances(mut found: Vec<FoundInstance>, primary: usize) -> Vec<AcsInstance> {
+	let primary_position = found.get(primary).map(|instance| instance.position);
+	let mut primary_marked = false;
+	found.sort_by_key(|instance| instance.position);
+
+	found
+		.into_iter()
+		.enumerate()
+		.map(|(order, instance)| {
+			let primary = !primary_marked && Some(instance.position) == primary_position;
+			primary_marked |= primary;
+			AcsInstance {
+				context: InstanceContext {
+					order,
+					tab: instance.tab,
+					collapsible: instance.collapsible,
+				},
+				primary,
+				acs: instance.acs,
+			}
+		})
+		.collect()
+}
+
+fn position(document: &Html, element: ElementRef) -> usize {
+	document
+		.tree
+		.root()
+		.descendants()
+		.position(|node| node.id() == element.id())
+		.unwrap_or(0)
+}
+
+// The innermost [[tab]] around the element, rendered as a div with an id like wiki-tab-0-1
+fn tab_title(element: ElementRef) -> Option<String> {
+	let tab = element
+		.ancestors()
+		.filter_map(ElementRef::wrap)
+		.find(|ancestor| {
+			ancestor
+				.value()
+				.id()
+				.is_some_and(|id| id.starts_with("wiki-tab-"))
+		})?;
+	page::tab_label(tab)
+}
+
+// The label of the innermost [[collapsible]] around the element
+fn collapsible_label(element: ElementRef) -> Option<String> {
+	let block = element
+		.ancestors()
+		.filter_map(ElementRef::wrap)
+		.find(|ancestor| {
+			ancestor
+				.value()
+				.classes()
+				.any(|class| class == "collapsible-block")
+		})?;
+	page::collapsible_label(block)
+}
+
+#[test]
+fn test_instances_round_trip() {
+	use crate::acs::SharedAcs;
+
+	let instance = AcsInstance {
+		context: InstanceContext {
+			order: 1,
+			tab: Some("Post-Incident".to_string()),
+			collapsible: None,
+		},
+		primary: false,
+		acs: Acs::Bar {
+			shared: SharedAcs {
+				contain: "keter".to_string(),
+				secondary: String::new(),
+				disrupt: "keneq".to_string(),
+				scraper: "ACS Bar".to_string(),
+			},
+			clearance: "LEVEL 4".to_string(),
+			clearance_text: "Secret".to_string(),
+			risk: "danger".to_string(),
+		},
+	};
+
+	let json = serde_json::to_string(&instance).unwrap();
+	let parsed: AcsInstance = serde_json::from_str(&json).unwrap();
+	assert_eq!(parsed.context, instance.context);
+	assert!(matches!(parsed.acs, Acs::Bar { ref risk, .. } if risk == "danger"));
+}
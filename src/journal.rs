@@ -0,0 +1,156 @@
+use anyhow::Result;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+	collections::HashMap,
+	fs::{self, File, OpenOptions},
+	io::{BufRead, BufReader, Write},
+	path::{Path, PathBuf},
+	sync::Mutex,
+};
+
+// The stages of a run that can be resumed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+	Scrape,
+	Backlinks,
+	CrossCompare,
+}
+
+impl Stage {
+	pub fn file_name(&self) -> &'static str {
+		match self {
+			Stage::Scrape => "scrape.journal.jsonl",
+			Stage::Backlinks => "backlinks.journal.jsonl",
+			Stage::CrossCompare => "cross_compare.journal.jsonl",
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+struct JournalLine<T> {
+	key: String,
+	value: T,
+}
+
+// Append-only record of the work a stage has finished, one JSON line per item. It is
+// removed once the stage has written its artifact, so a journal left on disk means the
+// run was interrupted
+pub struct Journal {
+	path: PathBuf,
+	file: Mutex<File>,
+}
+
+impl Journal {
+	// Opens the journal of a stage. When resuming, the items finished by the interrupted
+	// run are returned, otherwise any old journal is discarded
+	pub fn open<T: Serialize + DeserializeOwned>(
+		dir: impl AsRef<Path>,
+		stage: Stage,
+		resume: bool,
+	) -> Result<(Journal, HashMap<String, T>)> {
+		let dir = dir.as_ref();
+		fs::create_dir_all(dir)?;
+		let path = dir.join(stage.file_name());
+
+		let mut entries: Vec<JournalLine<T>> = Vec::new();
+		if resume && path.exists() {
+			for line in BufReader::new(File::open(&path)?).lines() {
+				// The last line is cut short when the run was killed mid write
+				match serde_json::from_str(&line?) {
+					Ok(entry) => entries.push(entry),
+					Err(e) => log::warn!(
+						"Skipping unreadable line in {}: {}",
+						path.display(),
+						e
+					),
+				}
+			}
+		}
+
+		// Rewrite the readable lines so new entries never follow a partial one
+		let temp_path = path.with_extension("jsonl.tmp");
+		let mut temp = File::create(&temp_path)?;
+		for entry in &entries {
+			writeln!(temp, "{}", serde_json::to_string(entry)?)?;
+		}
+		temp.sync_all()?;
+		fs::rename(&temp_path, &path)?;
+
+		let file = OpenOptions::new().append(true).open(&path)?;
+		let done = entries
+			.into_iter()
+			.map(|entry| (entry.key, entry.value))
+			.collect();
+
+		Ok((
+			Journal {
+				path,
+				file: Mutex::new(file),
+			},
+			done,
+		))
+	}
+
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	pub fn record<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
+		let line =
+			serde_json::to_string(&serde_json::json!({ "key": key, "value": value }))?;
+		let mut file = self.file.lock().unwrap();
+		writeln!(file, "{}", line)?;
+		file.flush()?;
+
+		Ok(())
+	}
+
+	// Logs instead of failing, losing a checkpoint only costs redoing the item
+	pub fn checkpoint<T: Serialize>(&self, key: &str, value: &T) {
+		if let Err(e) = self.record(key, value) {
+			log::error!("Failed to write {} to {}: {}", key, self.path.display(), e);
+		}
+	}
+
+	// Removes the journal once the stage's results are safely written
+	pub fn finish(self) -> Result<()> {
+		drop(self.file);
+		fs::remove_file(&self.path)?;
+
+		Ok(())
+	}
+}
+
+#[test]
+fn test_journal_resume() {
+	let dir =
+		std::env::temp_dir().join(format!("acs-journal-test-{}", std::process::id()));
+
+	let (journal, done) = Journal::open::<u16>(&dir, Stage::Scrape, true).unwrap();
+	assert!(done.is_empty());
+	journal.record("SCP-002", &2u16).unwrap();
+	journal.record("SCP-003", &3u16).unwrap();
+	drop(journal);
+
+	// Simulate a crash in the middle of writing a line
+	let path = dir.join(Stage::Scrape.file_name());
+	let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+	write!(file, "{{\"key\":\"SCP-0").unwrap();
+	drop(file);
+
+	let (journal, done) = Journal::open::<u16>(&dir, Stage::Scrape, true).unwrap();
+	assert_eq!(done.len(), 2);
+	assert_eq!(done["SCP-003"], 3);
+	journal.record("SCP-004", &4u16).unwrap();
+	drop(journal);
+
+	let (_, done) = Journal::open::<u16>(&dir, Stage::Scrape, true).unwrap();
+	assert_eq!(done.len(), 3);
+
+	let (journal, done) = Journal::open::<u16>(&dir, Stage::Scrape, false).unwrap();
+	assert!(done.is_empty());
+	journal.finish().unwrap();
+	assert!(!path.exists());
+
+	fs::remove_dir_all(&dir).unwrap();
+}
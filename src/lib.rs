@@ -0,0 +1,49 @@
+pub mod acs;
+pub mod ajax;
+pub mod backlinks;
+pub mod branch;
+pub mod cache;
+pub mod classes;
+pub mod components;
+pub mod config;
+pub mod designation;
+pub mod error;
+pub mod extract;
+pub mod http;
+pub mod incremental;
+pub mod instances;
+pub mod journal;
+pub mod names;
+pub mod output;
+pub mod page;
+pub mod query;
+pub mod retry;
+pub mod scraper;
+pub mod sort_json;
+pub mod source;
+pub mod text;
+pub mod validate;
+
+pub use acs::{Acs, BacklinksInfo, SCPInfo, SharedAcs};
+pub use backlinks::BacklinkComponent;
+pub use branch::{Branch, Keywords};
+pub use cache::{CacheMode, PageCache};
+pub use classes::{
+	AcsClasses, ClearanceLevel, ContainmentClass, DisruptionClass, RiskClass,
+};
+pub use components::{ComponentDefinition, ComponentSet};
+pub use config::Config;
+pub use designation::{SCPDesignation, SeriesKind};
+pub use error::{ErrorKind, ErrorReport, ScrapeError};
+pub use extract::{AcsExtractor, ExtractorRegistry};
+pub use http::{HttpClient, HttpConfig, HttpResponse};
+pub use incremental::PageMetadata;
+pub use instances::{AcsInstance, InstanceContext};
+pub use journal::{Journal, Stage};
+pub use names::NameIndex;
+pub use output::{Artifact, JsonFileSink, OutputSink};
+pub use page::{Location, PageStructure, Region};
+pub use query::Query;
+pub use retry::RetryPolicy;
+pub use scraper::{AcsScraper, ScraperConfig};
+pub use validate::{validate_database, ValidationIssue};
@@ -0,0 +1,302 @@
+use crate::acs::SCPInfo;
+use crate::branch::Branch;
+use crate::designation::SCPDesignation;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use scraper::{node::Node, ElementRef, Html, Selector};
+use std::collections::HashMap;
+
+// SCP Names Selectors
+static LI_SELECTOR: Lazy<Selector> =
+	Lazy::new(|| Selector::parse("[id*='toc']:not([id='toc0']) + ul li").unwrap());
+static LINK_SELECTOR: Lazy<Selector> = Lazy::new(|| Selector::parse("a").unwrap());
+
+static DASH_RGX: Lazy<Regex> = Lazy::new(|| Regex::new(r"-(\d{3,4})").unwrap());
+// The dash between the link and the name, a hyphen or an en or em dash
+static NAME_DASH_RGX: Lazy<Regex> =
+	Lazy::new(|| Regex::new(r"^\s*[-–—]\s*|\s[-–—]\s+").unwrap());
+
+// Series hubs are probed up to this one when the wiki never answers with a 404
+pub const MAX_SERIES: u16 = 99;
+
+// The hub of the Nth series. The first one has no number: scp-series, scp-series-2, ...
+pub fn series_hub_url(site_url: &str, series: u16) -> String {
+	let site_url = site_url.trim_end_matches('/');
+	match series {
+		1 => format!("{}/scp-series", site_url),
+		_ => format!("{}/scp-series-{}", site_url, series),
+	}
+}
+
+// A bare number after a dash, for links that don't spell out the designation. It is
+// taken to be one of the branch's own
+fn extract_dash_number(text: &str, branch: &Branch) -> String {
+	DASH_RGX
+		.captures(text)
+		.and_then(|cap| cap[1].parse::<u16>().ok())
+		.map(|number| branch.format_number(number))
+		.unwrap_or_default()
+}
+
+fn extract_designation(text: &str) -> String {
+	SCPDesignation::find(text)
+		.map(|designation| designation.to_string())
+		.unwrap_or_default()
+}
+
+// The link to the SCP itself. Items can also link to a hub, a tale or the authors, so
+// the first link with a designation is taken, falling back to the first link
+fn scp_link(li: ElementRef) -> Option<ElementRef> {
+	let mut links = li.select(&LINK_SELECTOR);
+	let first = links.next()?;
+	let has_designation = |link: &ElementRef| {
+		SCPDesignation::find(link.value().attr("href").unwrap_or("")).is_some()
+			|| SCPDesignation::find(&link_text(*link)).is_some()
+	};
+
+	if has_designation(&first) {
+		return Some(first);
+	}
+	Some(links.find(has_designation).unwrap_or(first))
+}
+
+// Text with entities decoded and whitespace collapsed
+fn link_text(link: ElementRef) -> String {
+	collapse_whitespace(&link.text().collect::<String>())
+}
+
+fn collapse_whitespace(text: &str) -> String {
+	text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn escape_html(text: &str) -> String {
+	text.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+}
+
+// The name after the SCP link and its dash, as plain text and as HTML keeping the
+// formatting. Empty when the item has no dash after the link
+fn split_name(li: ElementRef, link: ElementRef) -> (String, String) {
+	let mut after_link = false;
+	let mut in_name = false;
+	let mut text = String::new();
+	let mut html = String::new();
+
+	for child in li.children() {
+		if !after_link {
+			after_link = child.id() == link.id()
+				|| child.descendants().any(|node| node.id() == link.id());
+			continue;
+		}
+
+		match child.value() {
+			Node::Text(part) if !in_name => {
+				if let Some(dash) = NAME_DASH_RGX.find(part) {
+					in_name = true;
+					let rest = &part[dash.end()..];
+					text.push_str(rest);
+					html.push_str(&escape_html(rest));
+				}
+			}
+			Node::Text(part) => {
+				text.push_str(part);
+				html.push_str(&escape_html(part));
+			}
+			Node::Element(_) if in_name => {
+				if let Some(element) = ElementRef::wrap(child) {
+					text.extend(element.text());
+					html.push_str(&element.html());
+				}
+			}
+			_ => {}
+		}
+	}
+
+	(collapse_whitespace(&text), html.trim().to_string())
+}
+
+// Parses the list of SCPs on a series hub page of the branch
+pub fn parse_series_page(document: &Html, branch: &Branch) -> Vec<SCPInfo> {
+	let mut scp_names_vec: Vec<SCPInfo> = Vec::new();
+
+	for li in document.select(&LI_SELECTOR) {
+		if let Some(link) = scp_link(li) {
+			//Getting class of SCP Link
+			let link_class = link.value().attr("class").map(|s| s.to_string());
+			log::info!("scp link class {:?}", link_class);
+
+			//Checking if the Link's class is "newpage"
+			if link_class.as_deref() == Some("newpage") {
+				continue;
+			}
+
+			let link_url = link.value().attr("href").unwrap_or("");
+			let link_url_uppercase = link_url.to_uppercase();
+
+			//Finding SCP Number string either in URL href or displayed text
+			let actual_number = if link_url_uppercase.contains("SCP-")
+				|| link_url_uppercase.starts_with("SCP-")
+			{
+				extract_designation(link_url)
+			} else if link_text(link).to_uppercase().starts_with("SCP-") {
+				extract_designation(&link_text(link))
+			} else if link_url.contains('-') {
+				extract_dash_number(link_url, branch)
+			} else {
+				String::new()
+			};
+
+			//Finding displayed SCP Number which is sometimes unique
+			let display_number = link_text(link);
+
+			//Finding SCP Name after the link
+			let (name, name_html) = split_name(li, link);
+
+			//Getting SCP link URL
+			let url = branch.url(link_url);
+
+			log::info!(
+				"url: {:?}\nname {:?}\nactual number {:?}\ndisplay number {:?}",
+				url,
+				name,
+				actual_number,
+				display_number
+			);
+
+			//Pushing parsed vector to SCP Names
+			scp_names_vec.push(SCPInfo {
+				actual_number,
+				display_number,
+				name,
+				name_html,
+				url,
+			});
+		}
+	}
+
+	scp_names_vec
+}
+
+// The SCP names, loaded once and indexed by number, URL and name instead of searching the
+// whole list for every page
+#[derive(Debug, Clone, Default)]
+pub struct NameIndex {
+	entries: Vec<SCPInfo>,
+	by_number: HashMap<String, usize>,
+	by_url: HashMap<String, usize>,
+	by_name: HashMap<String, usize>,
+}
+
+impl NameIndex {
+	// When a key repeats, the first entry keeps it
+	pub fn new(entries: Vec<SCPInfo>) -> Self {
+		let mut by_number = HashMap::new();
+		let mut by_url = HashMap::new();
+		let mut by_name = HashMap::new();
+
+		for (index, info) in entries.iter().enumerate() {
+			by_number
+				.entry(info.actual_number.to_uppercase())
+				.or_insert(index);
+			by_url.entry(normalize_url(&info.url)).or_insert(index);
+			if !info.name.trim().is_empty() {
+				by_name.entry(normalize_name(&info.name)).or_insert(index);
+			}
+		}
+
+		NameIndex {
+			entries,
+			by_number,
+			by_url,
+			by_name,
+		}
+	}
+
+	pub fn entries(&self) -> &[SCPInfo] {
+		&self.entries
+	}
+
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	// The highest of the branch's own numbers, the last SCP of the newest series
+	pub fn max_number(&self, branch: &Branch) -> Option<u16> {
+		self.entries
+			.iter()
+			.filter_map(|info| info.actual_number.parse::<SCPDesignation>().ok())
+			.filter(|designation| branch.is_native(designation))
+			.map(|designation| designation.number)
+			.max()
+	}
+
+	pub fn by_number(&self, actual_number: &str) -> Option<&SCPInfo> {
+		self.get(self.by_number.get(&actual_number.to_uppercase()))
+	}
+
+	pub fn by_url(&self, url: &str) -> Option<&SCPInfo> {
+		self.get(self.by_url.get(&normalize_url(url)))
+	}
+
+	pub fn by_name(&self, name: &str) -> Option<&SCPInfo> {
+		self.get(self.by_name.get(&normalize_name(name)))
+	}
+
+	fn get(&self, index: Option<&usize>) -> Option<&SCPInfo> {
+		index.map(|&index| &self.entries[index])
+	}
+}
+
+// Ignores the scheme, letter case and a trailing slash
+fn normalize_url(url: &str) -> String {
+	let url = url.trim().to_lowercase();
+	let url = url
+		.strip_prefix("https://")
+		.or_else(|| url.strip_prefix("http://"))
+		.unwrap_or(&url);
+	url.trim_end_matches('/').to_string()
+}
+
+// Ignores letter case, surrounding quotes and runs of whitespace
+pub fn normalize_name(name: &str) -> String {
+	name.split_whitespace()
+		.collect::<Vec<_>>()
+		.join(" ")
+		.trim_matches(|c: char| c == '"' || c == '\'')
+		.to_lowercase()
+}
+
+#[test]
+fn test_name_index() {
+	let info = |number: &str, name: &str| {
+		let url = format!("https://scp-wiki.wikidot.com/{}", number.to_lowercase());
+		SCPInfo::new(number, name, &url)
+	};
+	let index = NameIndex::new(vec![
+		info("SCP-173", "The Sculpture"),
+		info("SCP-002", "The \"Living\" Room"),
+		info("SCP-173", "Duplicate"),
+		info("SCP-8900-EX", "The Sky Is Blue"),
+	]);
+
+	assert_eq!(index.len(), 4);
+	assert_eq!(index.max_number(&Branch::default()), Some(173));
+	assert_eq!(index.by_number("scp-173").unwrap().name, "The Sculpture");
+	assert_eq!(
+		index
+			.by_url("http://scp-wiki.wikidot.com/SCP-002/")
+			.unwrap()
+			.actual_number,
+		"SCP-002"
+	);
+	assert_eq!(
+		index.by_name("  the   sculpture ").unwrap().actual_number,
+		"SCP-173"
+	);
+	assert!(index.by_number("SCP-999").is_none());
+}
@@ -0,0 +1,155 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::{
+	collections::HashMap,
+	fs::{self, File},
+	io::BufReader,
+	path::{Path, PathBuf},
+};
+
+// The JSON files produced by a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Artifact {
+	Names,
+	Database,
+	Backlinks,
+	PageMetadata,
+}
+
+impl Artifact {
+	pub fn file_name(&self) -> &'static str {
+		match self {
+			Artifact::Names => "scp_names.json",
+			Artifact::Database => "acs_database.json",
+			Artifact::Backlinks => "acs_backlinks.json",
+			Artifact::PageMetadata => "page_metadata.json",
+		}
+	}
+}
+
+// Somewhere the scraper can write its artifacts to (and optionally read them back from)
+pub trait OutputSink: Send + Sync {
+	fn write(&self, artifact: Artifact, data: &Value) -> Result<()>;
+
+	fn read(&self, _artifact: Artifact) -> Result<Option<Value>> {
+		Ok(None)
+	}
+}
+
+// Writes every artifact as a pretty printed JSON file inside a directory
+#[derive(Debug, Clone)]
+pub struct JsonFileSink {
+	dir: PathBuf,
+	file_names: HashMap<Artifact, PathBuf>,
+}
+
+impl JsonFileSink {
+	pub fn new(dir: impl AsRef<Path>) -> Self {
+		JsonFileSink {
+			dir: dir.as_ref().to_path_buf(),
+			file_names: HashMap::new(),
+		}
+	}
+
+	// Overrides the default file name of an artifact. Relative names are resolved
+	// against the directory
+	pub fn with_file_name(mut self, artifact: Artifact, name: impl AsRef<Path>) -> Self {
+		self.file_names
+			.insert(artifact, name.as_ref().to_path_buf());
+		self
+	}
+
+	pub fn dir(&self) -> &Path {
+		&self.dir
+	}
+
+	pub fn path(&self, artifact: Artifact) -> PathBuf {
+		match self.file_names.get(&artifact) {
+			Some(name) => self.dir.join(name),
+			None => self.dir.join(artifact.file_name()),
+		}
+	}
+
+	// Creates the directories the artifacts go in and checks they can be written to, so a
+	// bad path fails before hours of scraping instead of after
+	pub fn prepare(&self) -> Result<()> {
+		let mut dirs: Vec<PathBuf> = vec![self.dir.clone()];
+		for artifact in [
+			Artifact::Names,
+			Artifact::Database,
+			Artifact::Backlinks,
+			Artifact::PageMetadata,
+		] {
+			if let Some(parent) = self.path(artifact).parent() {
+				if !dirs.iter().any(|dir| dir == parent) {
+					dirs.push(parent.to_path_buf());
+				}
+			}
+		}
+
+		for dir in dirs {
+			fs::create_dir_all(&dir).with_context(|| {
+				format!("Failed to create output directory {}", dir.display())
+			})?;
+
+			let probe = dir.join(format!(".write-test-{}", std::process::id()));
+			fs::write(&probe, b"").with_context(|| {
+				format!("Output directory {} is not writable", dir.display())
+			})?;
+			fs::remove_file(&probe)?;
+		}
+
+		Ok(())
+	}
+}
+
+impl Default for JsonFileSink {
+	fn default() -> Self {
+		JsonFileSink::new("output")
+	}
+}
+
+impl OutputSink for JsonFileSink {
+	fn write(&self, artifact: Artifact, data: &Value) -> Result<()> {
+		let file = File::create(self.path(artifact))?;
+		serde_json::to_writer_pretty(file, data)?;
+
+		Ok(())
+	}
+
+	fn read(&self, artifact: Artifact) -> Result<Option<Value>> {
+		let path = self.path(artifact);
+		if !path.exists() {
+			return Ok(None);
+		}
+
+		let file = File::open(path)?;
+		Ok(Some(serde_json::from_reader(BufReader::new(file))?))
+	}
+}
+
+#[test]
+fn test_json_file_sink_paths() {
+	let dir =
+		std::env::temp_dir().join(format!("acs-output-test-{}", std::process::id()));
+	let sink = JsonFileSink::new(&dir)
+		.with_file_name(Artifact::Database, "run-2/database.json")
+		.with_file_name(Artifact::Names, "names.json");
+
+	assert_eq!(sink.path(Artifact::Names), dir.join("names.json"));
+	assert_eq!(
+		sink.path(Artifact::Backlinks),
+		dir.join("acs_backlinks.json")
+	);
+
+	sink.prepare().unwrap();
+	sink.write(Artifact::Database, &serde_json::json!([]))
+		.unwrap();
+	assert_eq!(
+		sink.read(Artifact::Database).unwrap(),
+		Some(serde_json::json!([]))
+	);
+	assert_eq!(sink.read(Artifact::Names).unwrap(), None);
+
+	fs::remove_dir_all(&dir).unwrap();
+}
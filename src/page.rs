@@ -0,0 +1,240 @@
+use once_cell::sync::Lazy;
+use scraper::{
+	node::{Element, Node},
+	ElementRef, Html, Selector,
+};
+use serde::{Deserialize, Serialize};
+
+static PAGE_CONTENT_SELECTOR: Lazy<Selector> =
+	Lazy::new(|| Selector::parse("#page-content").unwrap());
+static TAB_NAV_SELECTOR: Lazy<Selector> =
+	Lazy::new(|| Selector::parse("ul.yui-nav > li").unwrap());
+static COLLAPSIBLE_LINK_SELECTOR: Lazy<Selector> = Lazy::new(|| {
+	Selector::parse("div.collapsible-block-folded a.collapsible-block-link").unwrap()
+});
+
+// Site furniture inside #page-content: the licensing box with "Cite this page", the
+// rating widget and the wikiwalk navigation
+const CHROME_CLASSES: [&str; 5] = [
+	"licensebox",
+	"page-rate-widget-box",
+	"creditRate",
+	"footer-wikiwalk-nav",
+	"info-container",
+];
+
+// The part of a wiki page a piece of text comes from
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Region {
+	// The article itself, outside any tab or collapsible
+	Main,
+	Tab,
+	Collapsible,
+	Footnotes,
+	// Everything the wiki adds around the article: header, side bar, footer, license box
+	Chrome,
+}
+
+// A region of the page, with the tab title or collapsible label it has
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+	pub region: Region,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub label: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Section {
+	pub location: Location,
+	pub text: String,
+}
+
+// The text of a page split into its regions. Text in a tab or collapsible belongs to the
+// innermost one, everything else in #page-content is the main content
+#[derive(Debug, Clone)]
+pub struct PageStructure {
+	pub sections: Vec<Section>,
+}
+
+impl PageStructure {
+	pub fn parse(document: &Html) -> Self {
+		// Pages saved without the wiki's layout are all content
+		let outer = match document.select(&PAGE_CONTENT_SELECTOR).next() {
+			Some(_) => Region::Chrome,
+			None => Region::Main,
+		};
+		let mut structure = PageStructure {
+			sections: vec![Section {
+				location: Location {
+					region: outer,
+					label: None,
+				},
+				text: String::new(),
+			}],
+		};
+		structure.walk(document.root_element(), 0);
+		structure
+	}
+
+	// The sections of a region, in page order
+	pub fn sections(&self, region: Region) -> impl Iterator<Item = &Section> {
+		self.sections
+			.iter()
+			.filter(move |section| section.location.region == region)
+	}
+
+	fn walk(&mut self, node: ElementRef, current: usize) {
+		let element = node.value();
+		if matches!(element.name(), "script" | "style")
+			|| has_class(element, "yui-nav")
+			|| has_class(element, "collapsible-block-folded")
+			|| has_class(element, "collapsible-block-unfolded-link")
+		{
+			return;
+		}
+
+		let location = if self.sections[current].location.region == Region::Chrome
+			&& element.id() != Some("page-content")
+		{
+			None
+		} else if element.id() == Some("page-content") {
+			Some(Location {
+				region: Region::Main,
+				label: None,
+			})
+		} else if is_chrome(element) {
+			Some(Location {
+				region: Region::Chrome,
+				label: None,
+			})
+		} else if has_class(element, "footnotes-footer") {
+			Some(Location {
+				region: Region::Footnotes,
+				label: None,
+			})
+		} else if element.id().is_some_and(|id| id.starts_with("wiki-tab-")) {
+			Some(Location {
+				region: Region::Tab,
+				label: tab_label(node),
+			})
+		} else if has_class(element, "collapsible-block") {
+			Some(Location {
+				region: Region::Collapsible,
+				label: collapsible_label(node),
+			})
+		} else {
+			None
+		};
+
+		let section = match location {
+			Some(location) => {
+				self.sections.push(Section {
+					location,
+					text: String::new(),
+				});
+				self.sections.len() - 1
+			}
+			None => current,
+		};
+		for child in node.children() {
+			match child.value() {
+				Node::Text(text) => self.sections[section].text.push_str(text),
+				Node::Element(_) => self.walk(ElementRef::wrap(child).unwrap(), section),
+				_ => {}
+			}
+		}
+		// Keeps the words on either side of a nested region apart
+		if section != current {
+			self.sections[current].text.push('\n');
+		}
+	}
+}
+
+fn has_class(element: &Element, name: &str) -> bool {
+	element.classes().any(|class| class == name)
+}
+
+pub fn is_chrome(element: &Element) -> bool {
+	CHROME_CLASSES.iter().any(|class| has_class(element, class))
+}
+
+// The title of a tab, a div with an id like wiki-tab-0-1 that sits next to the list of
+// tab titles in the same order
+pub fn tab_label(tab: ElementRef) -> Option<String> {
+	let index = tab.prev_siblings().filter_map(ElementRef::wrap).count();
+	let navset = tab
+		.ancestors()
+		.filter_map(ElementRef::wrap)
+		.find(|ancestor| has_class(ancestor.value(), "yui-navset"))?;
+	let title = navset.select(&TAB_NAV_SELECTOR).nth(index)?;
+
+	Some(title.text().collect::<String>().trim().to_string())
+}
+
+// The label on the folded link of a div.collapsible-block
+pub fn collapsible_label(block: ElementRef) -> Option<String> {
+	let link = block.select(&COLLAPSIBLE_LINK_SELECTOR).next()?;
+
+	Some(link.text().collect::<String>().trim().to_string())
+}
+
+#[test]
+fn test_page_structure() {
+	let document = Html::parse_document(
+		r#"<html><body>
+		<div id="header">SCP Foundation</div>
+		<div id="page-content">
+			<p>Containment Class: Keter</p>
+			<div class="yui-navset">
+				<ul class="yui-nav"><li><em>Before</em></li><li><em>After</em></li></ul>
+				<div class="yui-content">
+					<div id="wiki-tab-0-0"><p>Risk Class: Notice</p></div>
+					<div id="wiki-tab-0-1"><p>Risk Class: Danger</p></div>
+				</div>
+			</div>
+			<div class="collapsible-block">
+				<div class="collapsible-block-folded"><a class="collapsible-block-link">+ Addendum</a></div>
+				<div class="collapsible-block-unfolded">
+					<div class="collapsible-block-content"><p>Disruption Class: Vlam</p></div>
+				</div>
+			</div>
+			<div class="footnotes-footer"><div class="footnote-footer">1. Secondary Class: Thaumiel</div></div>
+			<div class="licensebox"><p>Cite this page as: Keter</p></div>
+		</div>
+		<div id="footer">Licensed under CC BY-SA 3.0</div>
+		</body></html>"#,
+	);
+	let page = PageStructure::parse(&document);
+
+	let text = |region| {
+		page.sections(region)
+			.map(|section| {
+				section
+					.text
+					.split_whitespace()
+					.collect::<Vec<_>>()
+					.join(" ")
+			})
+			.collect::<Vec<_>>()
+	};
+	assert_eq!(text(Region::Main), vec!["Containment Class: Keter"]);
+	assert_eq!(
+		text(Region::Tab),
+		vec!["Risk Class: Notice", "Risk Class: Danger"]
+	);
+	assert_eq!(text(Region::Collapsible), vec!["Disruption Class: Vlam"]);
+	assert_eq!(
+		text(Region::Footnotes),
+		vec!["1. Secondary Class: Thaumiel"]
+	);
+	assert!(text(Region::Chrome).concat().contains("Cite this page"));
+	assert!(text(Region::Chrome).concat().contains("Licensed under"));
+
+	let labels: Vec<_> = page
+		.sections
+		.iter()
+		.filter_map(|section| section.location.label.as_deref())
+		.collect();
+	assert_eq!(labels, vec!["Before", "After", "+ Addendum"]);
+}
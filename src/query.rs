@@ -0,0 +1,113 @@
+use crate::acs::Acs;
+use crate::classes::{ClearanceLevel, ContainmentClass, DisruptionClass, RiskClass};
+
+// Filters database entries. Every field that is set has to match
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+	pub number: Option<String>,
+	// Case insensitive substring of the name
+	pub name: Option<String>,
+	pub contain: Option<ContainmentClass>,
+	pub secondary: Option<ContainmentClass>,
+	pub disrupt: Option<DisruptionClass>,
+	pub risk: Option<RiskClass>,
+	pub clearance: Option<ClearanceLevel>,
+	// Name of the extractor that found the entry
+	pub scraper: Option<String>,
+	pub fragment: Option<bool>,
+}
+
+impl Query {
+	pub fn matches(&self, acs: &Acs) -> bool {
+		let Acs::Vanilla {
+			shared,
+			name,
+			actual_number,
+			fragment,
+			..
+		} = acs
+		else {
+			return false;
+		};
+		let classes = acs.normalized_classes();
+
+		self.number
+			.as_ref()
+			.is_none_or(|number| number.eq_ignore_ascii_case(actual_number))
+			&& self
+				.name
+				.as_ref()
+				.is_none_or(|query| name.to_lowercase().contains(&query.to_lowercase()))
+			&& (self.contain.is_none() || self.contain == classes.contain)
+			&& (self.secondary.is_none() || self.secondary == classes.secondary)
+			&& (self.disrupt.is_none() || self.disrupt == classes.disrupt)
+			&& (self.risk.is_none() || self.risk == classes.risk)
+			&& (self.clearance.is_none() || self.clearance == classes.clearance)
+			&& self
+				.scraper
+				.as_ref()
+				.is_none_or(|scraper| scraper.eq_ignore_ascii_case(&shared.scraper))
+			&& self.fragment.is_none_or(|query| query == *fragment)
+	}
+
+	pub fn filter<'a>(&self, entries: &'a [Acs]) -> Vec<&'a Acs> {
+		entries.iter().filter(|acs| self.matches(acs)).collect()
+	}
+}
+
+#[test]
+fn test_query() {
+	use crate::acs::{create_acs, SharedAcs};
+
+	let entry = |number: &str, contain: &str, disrupt: &str| {
+		create_acs(Acs::Vanilla {
+			shared: SharedAcs {
+				contain: contain.to_string(),
+				secondary: String::new(),
+				disrupt: disrupt.to_string(),
+				scraper: "ACS Bar".to_string(),
+			},
+			name: format!("{} Name", number),
+			actual_number: number.to_string(),
+			display_number: String::new(),
+			clearance: "LEVEL 3".to_string(),
+			clearance_text: String::new(),
+			risk: "danger".to_string(),
+			url: format!("https://scp-wiki.wikidot.com/{}", number.to_lowercase()),
+			fragment: false,
+			classes: Default::default(),
+			instances: Vec::new(),
+			regions: Default::default(),
+		})
+		.unwrap()
+	};
+	let entries = vec![
+		entry("SCP-002", "keter", "amida"),
+		entry("SCP-003", "Keter", "2/Vlam"),
+		entry("SCP-004", "safe", "dark"),
+	];
+
+	let keter = Query {
+		contain: Some(ContainmentClass::Keter),
+		..Query::default()
+	};
+	assert_eq!(keter.filter(&entries).len(), 2);
+
+	let vlam = Query {
+		contain: Some(ContainmentClass::Keter),
+		disrupt: Some(DisruptionClass::Vlam),
+		..Query::default()
+	};
+	assert_eq!(
+		vlam.filter(&entries)[0].url(),
+		Some("https://scp-wiki.wikidot.com/scp-003")
+	);
+
+	let name = Query {
+		name: Some("scp-004 name".to_string()),
+		clearance: Some(ClearanceLevel::Confidential),
+		..Query::default()
+	};
+	assert_eq!(name.filter(&entries).len(), 1);
+	assert_eq!(Query::default().filter(&entries).len(), 3);
+}
@@ -0,0 +1,188 @@
+use rand::Rng;
+use reqwest::StatusCode;
+use std::{
+	collections::BTreeMap,
+	sync::Mutex,
+	time::{Duration, Instant},
+};
+
+// How failed requests are retried. Applied by the HttpClient to every network call
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+	// Total attempts per request, including the first one
+	pub max_attempts: u32,
+	pub initial_backoff: Duration,
+	pub max_backoff: Duration,
+	pub multiplier: f64,
+	// Fraction of each backoff that is randomized, 0 waits the exact backoff
+	pub jitter: f64,
+	// Give up once this much time has been spent on a single request
+	pub max_total_time: Duration,
+	pub retryable_statuses: Vec<u16>,
+}
+
+impl Default for RetryPolicy {
+	fn default() -> Self {
+		RetryPolicy {
+			max_attempts: 6,
+			initial_backoff: Duration::from_secs(1),
+			max_backoff: Duration::from_secs(60),
+			multiplier: 2.0,
+			jitter: 0.5,
+			max_total_time: Duration::from_secs(300),
+			retryable_statuses: vec![408, 425, 429, 500, 502, 503, 504],
+		}
+	}
+}
+
+impl RetryPolicy {
+	// A policy that sends every request exactly once
+	pub fn none() -> Self {
+		RetryPolicy {
+			max_attempts: 1,
+			..RetryPolicy::default()
+		}
+	}
+
+	pub fn is_retryable_status(&self, status: StatusCode) -> bool {
+		self.retryable_statuses.contains(&status.as_u16())
+	}
+
+	// Timeouts, connection failures and bodies cut off halfway are worth another try, a
+	// malformed request is not. Reading a whole body wraps its failure in a decode error
+	pub fn is_retryable_error(&self, error: &reqwest::Error) -> bool {
+		let body_failed = error.is_body()
+			|| std::error::Error::source(error)
+				.and_then(|source| source.downcast_ref::<reqwest::Error>())
+				.is_some_and(|source| source.is_body());
+		error.is_timeout() || error.is_connect() || body_failed
+	}
+
+	// Backoff before the retry following `attempt` (1 based), without jitter
+	pub fn backoff(&self, attempt: u32) -> Duration {
+		let exponent = attempt.saturating_sub(1).min(32) as i32;
+		let seconds = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+		Duration::from_secs_f64(seconds.min(self.max_backoff.as_secs_f64()))
+	}
+
+	fn with_jitter(&self, backoff: Duration) -> Duration {
+		let jitter = self.jitter.clamp(0.0, 1.0);
+		if jitter == 0.0 {
+			return backoff;
+		}
+
+		let fixed = backoff.mul_f64(1.0 - jitter);
+		let random = backoff.mul_f64(jitter * rand::thread_rng().gen::<f64>());
+		fixed + random
+	}
+
+	// How long to wait before the next attempt, or None when the request should be given
+	// up on. `suggested` is a wait asked for by the server through Retry-After
+	pub fn next_delay(
+		&self,
+		attempt: u32,
+		started: Instant,
+		suggested: Option<Duration>,
+	) -> Option<Duration> {
+		if attempt >= self.max_attempts {
+			return None;
+		}
+
+		let delay = suggested.unwrap_or_else(|| self.with_jitter(self.backoff(attempt)));
+		if started.elapsed() + delay > self.max_total_time {
+			return None;
+		}
+
+		Some(delay)
+	}
+}
+
+// What the requests to a URL took. A URL requested more than once adds up its attempts
+// and keeps the outcome of the last request
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UrlAttempts {
+	pub attempts: u32,
+	// Why each retried attempt failed, e.g. "503 Service Unavailable" or "timed out"
+	pub retried_for: Vec<String>,
+	// The status of the last attempt, or the error it failed with
+	pub outcome: String,
+}
+
+// Attempts spent on every URL during the run
+#[derive(Debug, Default)]
+pub struct AttemptLog {
+	attempts: Mutex<BTreeMap<String, UrlAttempts>>,
+}
+
+impl AttemptLog {
+	pub fn record(
+		&self,
+		url: &str,
+		attempts: u32,
+		retried_for: Vec<String>,
+		outcome: &str,
+	) {
+		let mut log = self.attempts.lock().unwrap();
+		let entry = log.entry(url.to_string()).or_default();
+		entry.attempts += attempts;
+		entry.retried_for.extend(retried_for);
+		entry.outcome = outcome.to_string();
+	}
+
+	pub fn snapshot(&self) -> BTreeMap<String, UrlAttempts> {
+		self.attempts.lock().unwrap().clone()
+	}
+
+	// URLs that needed more than one attempt
+	pub fn retried(&self) -> Vec<(String, UrlAttempts)> {
+		self.attempts
+			.lock()
+			.unwrap()
+			.iter()
+			.filter(|(_, attempts)| attempts.attempts > 1)
+			.map(|(url, attempts)| (url.clone(), attempts.clone()))
+			.collect()
+	}
+}
+
+#[test]
+fn test_retry_policy() {
+	let policy = RetryPolicy {
+		max_attempts: 4,
+		initial_backoff: Duration::from_secs(1),
+		max_backoff: Duration::from_secs(5),
+		jitter: 0.0,
+		..RetryPolicy::default()
+	};
+	let started = Instant::now();
+
+	assert_eq!(policy.backoff(1), Duration::from_secs(1));
+	assert_eq!(policy.backoff(3), Duration::from_secs(4));
+	assert_eq!(policy.backoff(10), Duration::from_secs(5));
+
+	assert_eq!(
+		policy.next_delay(2, started, None),
+		Some(Duration::from_secs(2))
+	);
+	assert_eq!(
+		policy.next_delay(1, started, Some(Duration::from_secs(9))),
+		Some(Duration::from_secs(9))
+	);
+	assert_eq!(policy.next_delay(4, started, None), None);
+	assert_eq!(
+		policy.next_delay(1, started, Some(Duration::from_secs(600))),
+		None
+	);
+
+	assert!(policy.is_retryable_status(StatusCode::BAD_GATEWAY));
+	assert!(!policy.is_retryable_status(StatusCode::NOT_FOUND));
+
+	let jittered = RetryPolicy {
+		jitter: 0.5,
+		..policy
+	};
+	for _ in 0..20 {
+		let delay = jittered.next_delay(3, started, None).unwrap();
+		assert!(delay >= Duration::from_secs(2) && delay <= Duration::from_secs(4));
+	}
+}
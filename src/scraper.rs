@@ -0,0 +1,915 @@
+use crate::acs::{finish_acs, Acs, BacklinksInfo, SCPInfo};
+use crate::backlinks::{self, default_backlink_components, BacklinkComponent};
+use crate::branch::Branch;
+use crate::cache::{CachedPage, PageCache};
+use crate::components::ComponentSet;
+use crate::designation::{SCPDesignation, SeriesKind};
+use crate::error::{ErrorReport, ScrapeError};
+use crate::extract::{AcsExtractor, ExtractorRegistry};
+use crate::http::HttpClient;
+use crate::incremental::PageMetadata;
+use crate::instances::AcsInstance;
+use crate::journal::{Journal, Stage};
+use crate::names::{parse_series_page, series_hub_url, NameIndex, MAX_SERIES};
+use crate::output::{Artifact, JsonFileSink, OutputSink};
+use crate::sort_json;
+use crate::source;
+use anyhow::{anyhow, Result};
+use futures::stream::{FuturesUnordered, StreamExt};
+use indicatif::{ProgressBar, ProgressStyle};
+use log::error;
+use reqwest::header::{HeaderMap, HeaderValue};
+use scraper::Html;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+	collections::HashMap,
+	path::{Path, PathBuf},
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Arc, RwLock,
+	},
+	time::Duration,
+};
+use tokio::sync::Semaphore;
+
+// Pages are requested as if navigated to from the front page of the branch's site
+fn referer_headers(site_url: &str) -> HeaderMap {
+	let mut headers = HeaderMap::new();
+	let referer = format!("{}/", site_url.trim_end_matches('/'));
+	match HeaderValue::from_str(&referer) {
+		Ok(referer) => {
+			headers.insert(reqwest::header::REFERER, referer);
+		}
+		Err(e) => log::warn!("No Referer sent, {} is not a header value: {}", referer, e),
+	}
+	headers
+}
+
+#[derive(Debug, Clone)]
+pub struct ScraperConfig {
+	pub start: u16,
+	// None scrapes up to the highest number in the name index
+	pub end: Option<u16>,
+	pub limit: u16,
+	// Only refetch pages whose entry is older than max_age, and only re-scrape the ones
+	// that changed since, merging them into the existing database
+	pub incremental: bool,
+	pub max_age: Duration,
+	// Continue an interrupted scrape, backlinks fetch or cross-compare from its journal
+	pub resume: bool,
+	// Component pages whose backlinks are fetched
+	pub backlink_components: Vec<BacklinkComponent>,
+	// How many component or theme pages deep the pages wrapping a component are followed
+	pub backlink_depth: usize,
+	// The wiki that is scraped, its series hubs, number format and vocabulary
+	pub branch: Branch,
+	// Read the ACS arguments from the page source before falling back to the HTML
+	pub use_source: bool,
+	// Series outside the numbered ones whose hubs are added to the names and whose
+	// entries in the range are scraped, e.g. SCP-1234-J
+	pub include: Vec<SeriesKind>,
+}
+
+impl Default for ScraperConfig {
+	fn default() -> Self {
+		ScraperConfig {
+			start: 1,
+			end: None,
+			limit: 10,
+			incremental: false,
+			max_age: Duration::from_secs(7 * 24 * 60 * 60),
+			resume: false,
+			backlink_components: default_backlink_components(),
+			backlink_depth: 1,
+			branch: Branch::default(),
+			use_source: true,
+			include: Vec::new(),
+		}
+	}
+}
+
+// Entry point for library users: owns the HTTP client, the run configuration and
+// the sinks every artifact is written to
+pub struct AcsScraper {
+	http: Arc<HttpClient>,
+	config: ScraperConfig,
+	extractors: ExtractorRegistry,
+	cache: Arc<PageCache>,
+	sinks: Vec<Arc<dyn OutputSink>>,
+	journal_dir: PathBuf,
+	errors: ErrorReport,
+	// Loaded from the names artifact on first use
+	names: RwLock<Option<Arc<NameIndex>>>,
+}
+
+impl AcsScraper {
+	pub fn new(config: ScraperConfig) -> Self {
+		let extractors = ExtractorRegistry::from_components_with_keywords(
+			&ComponentSet::builtin(),
+			&config.branch.keywords,
+		)
+		.expect("Built-in component selectors are invalid");
+
+		AcsScraper {
+			http: Arc::new(HttpClient::default()),
+			config,
+			extractors,
+			cache: Arc::new(PageCache::default()),
+			sinks: vec![Arc::new(JsonFileSink::default())],
+			journal_dir: PathBuf::from("output"),
+			errors: ErrorReport::default(),
+			names: RwLock::new(None),
+		}
+	}
+
+	pub fn with_http(mut self, http: HttpClient) -> Self {
+		self.http = Arc::new(http);
+		self
+	}
+
+	pub fn with_extractors(mut self, extractors: ExtractorRegistry) -> Self {
+		self.extractors = extractors;
+		self
+	}
+
+	// Adds an extractor on top of the ones already registered
+	pub fn register_extractor(mut self, extractor: impl AcsExtractor + 'static) -> Self {
+		self.extractors.register(extractor);
+		self
+	}
+
+	pub fn with_cache(mut self, cache: PageCache) -> Self {
+		self.cache = Arc::new(cache);
+		self
+	}
+
+	// Replaces the default `output/` JSON files with the given sinks
+	pub fn with_sinks(mut self, sinks: Vec<Arc<dyn OutputSink>>) -> Self {
+		self.sinks = sinks;
+		self
+	}
+
+	// Directory the checkpoint journals of interrupted stages are kept in
+	pub fn with_journal_dir(mut self, dir: impl AsRef<Path>) -> Self {
+		self.journal_dir = dir.as_ref().to_path_buf();
+		self
+	}
+
+	// Uses the given names instead of loading them from the sinks
+	pub fn with_names(self, names: NameIndex) -> Self {
+		*self.names.write().unwrap() = Some(Arc::new(names));
+		self
+	}
+
+	pub fn config(&self) -> &ScraperConfig {
+		&self.config
+	}
+
+	pub fn http(&self) -> &HttpClient {
+		&self.http
+	}
+
+	// Failures of the run so far, grouped by kind
+	pub fn errors(&self) -> &ErrorReport {
+		&self.errors
+	}
+
+	fn open_journal<T: Serialize + DeserializeOwned>(
+		&self,
+		stage: Stage,
+	) -> Result<(Journal, HashMap<String, T>)> {
+		let (journal, done) =
+			Journal::open(&self.journal_dir, stage, self.config.resume)?;
+		if !done.is_empty() {
+			log::info!(
+				"Resuming from {}: {} items already done",
+				journal.path().display(),
+				done.len()
+			);
+		}
+
+		Ok((journal, done))
+	}
+
+	// The SCP names, read from the sinks the first time they are needed
+	pub fn names(&self) -> Result<Arc<NameIndex>> {
+		self.try_names()?
+			.ok_or_else(|| anyhow!("No sink holds {}", Artifact::Names.file_name()))
+	}
+
+	// The SCP names, or None when no sink holds them yet
+	fn try_names(&self) -> Result<Option<Arc<NameIndex>>> {
+		if let Some(names) = self.names.read().unwrap().as_ref() {
+			return Ok(Some(Arc::clone(names)));
+		}
+
+		let mut names = self.names.write().unwrap();
+		if let Some(names) = names.as_ref() {
+			return Ok(Some(Arc::clone(names)));
+		}
+		let Some(entries) = self.try_load(Artifact::Names)? else {
+			return Ok(None);
+		};
+		let index = Arc::new(NameIndex::new(entries));
+		*names = Some(Arc::clone(&index));
+		Ok(Some(index))
+	}
+
+	fn write<T: Serialize>(&self, artifact: Artifact, data: &[T]) -> Result<()> {
+		let json = serde_json::to_value(data)?;
+		for sink in &self.sinks {
+			sink.write(artifact, &json)?;
+		}
+
+		Ok(())
+	}
+
+	// Reads an artifact back from the first sink that has it
+	pub fn load<T: DeserializeOwned>(&self, artifact: Artifact) -> Result<Vec<T>> {
+		self.try_load(artifact)?
+			.ok_or_else(|| anyhow!("No sink holds {}", artifact.file_name()))
+	}
+
+	fn try_load<T: DeserializeOwned>(
+		&self,
+		artifact: Artifact,
+	) -> Result<Option<Vec<T>>> {
+		for sink in &self.sinks {
+			if let Some(json) = sink.read(artifact)? {
+				return Ok(Some(serde_json::from_value(json)?));
+			}
+		}
+
+		Ok(None)
+	}
+
+	// Fetches a page through the cache, a stale cached copy is fetched again on refresh
+	async fn fetch_page(
+		&self,
+		url: &str,
+		refresh: bool,
+	) -> Result<CachedPage, ScrapeError> {
+		let request = format!("GET {}", url);
+		let fetch = || async {
+			let headers = referer_headers(&self.config.branch.site_url);
+			let response = self.http.get_with_headers(url, &headers).await?;
+			Ok(CachedPage::from_response(&request, response))
+		};
+		let page = if refresh {
+			self.cache.refresh(&request, fetch).await
+		} else {
+			self.cache.fetch(&request, fetch).await
+		}
+		.map_err(|e| match self.cache.mode().uses_network() {
+			true => ScrapeError::from_request(url, e),
+			false => ScrapeError::Other(e),
+		})?;
+
+		let Some(page) = page else {
+			return Err(ScrapeError::NotCached {
+				url: url.to_string(),
+			});
+		};
+
+		if page.is_not_found() {
+			return Err(ScrapeError::NotFound {
+				url: url.to_string(),
+			});
+		} else if !page.is_success() {
+			return Err(ScrapeError::Status {
+				url: url.to_string(),
+				status: page.status,
+			});
+		}
+
+		Ok(page)
+	}
+
+	pub async fn request_page(&self, url: &str) -> Result<Html, ScrapeError> {
+		let page = self.fetch_page(url, false).await?;
+		Ok(Html::parse_document(&page.body))
+	}
+
+	// Scrape SCP Series Pages -> Get SCP Names -> Write them to json File
+	pub async fn init_scp_names_json(&self) -> Result<Vec<SCPInfo>> {
+		let mut scp_names_vec: Vec<SCPInfo> = Vec::new();
+
+		let progress_bar_scp_names = ProgressBar::new_spinner();
+		progress_bar_scp_names.set_style(
+			ProgressStyle::default_bar()
+				.template("{msg} {spinner:.green} {pos:>7}")
+				.expect("Failed to set progress bar style.")
+				.progress_chars("=> "),
+		);
+		progress_bar_scp_names.set_message("Initializing SCP Info");
+
+		if self.config.branch.series_urls.is_empty() {
+			// The first hub the wiki says is missing is the end of the series. A hub missing
+			// from the cache says nothing about the wiki, so it is an error
+			let mut hubs = 0;
+			for series in 1..=MAX_SERIES {
+				let series_url = series_hub_url(&self.config.branch.site_url, series);
+				match self.request_page(&series_url).await {
+					Ok(document) => {
+						let entries = parse_series_page(&document, &self.config.branch);
+						progress_bar_scp_names.inc(entries.len() as u64);
+						scp_names_vec.extend(entries);
+						hubs += 1;
+					}
+					Err(ScrapeError::NotFound { .. }) => break,
+					Err(e) => return Err(e.into()),
+				}
+			}
+			if hubs == 0 {
+				return Err(anyhow!(
+					"No series hubs found, {} doesn't exist",
+					series_hub_url(&self.config.branch.site_url, 1)
+				));
+			}
+			log::info!("Found {} series hubs", hubs);
+		}
+
+		let series_urls = self.config.branch.series_urls.iter().cloned().chain(
+			self.config
+				.include
+				.iter()
+				.map(|kind| kind.hub_url(&self.config.branch.site_url)),
+		);
+		for series_url in series_urls {
+			match self.request_page(&series_url).await {
+				Ok(document) => {
+					let entries = parse_series_page(&document, &self.config.branch);
+					progress_bar_scp_names.inc(entries.len() as u64);
+					scp_names_vec.extend(entries);
+				}
+				Err(
+					e @ (ScrapeError::NotFound { .. } | ScrapeError::NotCached { .. }),
+				) => {
+					log::warn!("Series url not found: {}", series_url);
+					self.errors.record(&e);
+				}
+				Err(e) => return Err(e.into()),
+			}
+		}
+
+		sort_json::sort(&mut scp_names_vec, "actual_number");
+		self.write(Artifact::Names, &scp_names_vec)?;
+		*self.names.write().unwrap() =
+			Some(Arc::new(NameIndex::new(scp_names_vec.clone())));
+
+		progress_bar_scp_names.finish_with_message("SCP Info Initialized");
+		Ok(scp_names_vec)
+	}
+
+	// Get SCP Name from the name index based on Number
+	pub async fn get_name_display_number(
+		&self,
+		actual_number: &str,
+	) -> Result<(String, String), ScrapeError> {
+		self.names()?
+			.by_number(actual_number)
+			.map(|scp_info| {
+				(scp_info.name.to_owned(), scp_info.display_number.to_owned())
+			})
+			.ok_or_else(|| ScrapeError::NameNotFound {
+				actual_number: actual_number.to_string(),
+			})
+	}
+
+	// Fetches a page and scrapes it for ACS data
+	pub async fn fetch_acs_data(
+		&self,
+		actual_number: &str,
+		name: Option<&str>,
+		url: &str,
+		fragment: &bool,
+	) -> Result<Acs, ScrapeError> {
+		log::debug!("Fetching data from: {}", url);
+		let document = self.request_page(url).await?;
+		self.extract_acs(&document, actual_number, name, url, fragment, false)
+			.await
+	}
+
+	// Scrapes an already fetched page for ACS data. `refresh` is whether the page was
+	// fetched again, so its source is fetched again too
+	async fn extract_acs(
+		&self,
+		document: &Html,
+		actual_number: &str,
+		mut name: Option<&str>,
+		url: &str,
+		fragment: &bool,
+		refresh: bool,
+	) -> Result<Acs, ScrapeError> {
+		let name_string: String;
+		let mut display_number: String = String::new();
+
+		if !actual_number.eq_ignore_ascii_case("scp-000")
+			&& !actual_number.eq_ignore_ascii_case("scp-001")
+			&& (name.is_none_or(|n| n.is_empty())
+				&& (actual_number.contains("scp-") || actual_number.contains("SCP-")))
+		{
+			let (fetched_name, fetched_display_number) =
+				self.get_name_display_number(actual_number).await?;
+			name_string = fetched_name;
+			name = Some(&name_string);
+
+			display_number = fetched_display_number;
+		}
+
+		let instances = match self.extract_from_source(document, url, refresh).await {
+			Some(instances) => instances,
+			None => self.extractors.extract_instances(document),
+		};
+		let acs = finish_acs(
+			&self.config.branch,
+			instances,
+			name.unwrap_or(""),
+			actual_number,
+			&display_number,
+			url,
+			fragment,
+		)
+		.map_err(|e| ScrapeError::Parse {
+			url: url.to_string(),
+			message: e.to_string(),
+		})?;
+
+		acs.ok_or_else(|| {
+			log::debug!("No data retrieved by any extractor for URL: {}", url);
+			ScrapeError::NoAcs {
+				url: url.to_string(),
+			}
+		})
+	}
+
+	// Reads the ACS Bar arguments from the page source. Any failure falls back to the
+	// HTML extractors, so it is only logged
+	async fn extract_from_source(
+		&self,
+		document: &Html,
+		url: &str,
+		refresh: bool,
+	) -> Option<Vec<AcsInstance>> {
+		if !self.config.use_source {
+			return None;
+		}
+		let Some(page_id) = source::page_id(document) else {
+			log::debug!("No page_id found on {}", url);
+			return None;
+		};
+
+		match source::fetch_page_source(
+			&self.http,
+			&self.cache,
+			&self.config.branch.site_url,
+			&page_id,
+			refresh,
+		)
+		.await
+		{
+			Ok(Some(source)) => Some(source::extract_instances_from_source(&source))
+				.filter(|instances| !instances.is_empty()),
+			Ok(None) => None,
+			Err(e) => {
+				log::warn!("Failed to fetch the source of {}: {}", url, e);
+				None
+			}
+		}
+	}
+
+	// Logs a failure at a level matching how unexpected it is and adds it to the report
+	fn report(&self, function: &str, error: &ScrapeError) {
+		match error {
+			ScrapeError::NotFound { .. } | ScrapeError::NotCached { .. } => {
+				log::warn!("f: {} | {}", function, error)
+			}
+			ScrapeError::NoAcs { .. } => log::debug!("f: {} | {}", function, error),
+			_ => error!("f: {} | {}", function, error),
+		}
+		self.errors.record(error);
+	}
+
+	// Scrapes a single SCP page, or skips it when incremental and the page is recent
+	// enough or unchanged
+	async fn scrape_page(
+		&self,
+		actual_number: &str,
+		url: &str,
+		previous: Option<&PageMetadata>,
+	) -> PageOutcome {
+		let ScraperConfig {
+			incremental,
+			max_age,
+			..
+		} = self.config;
+		let previous = previous.filter(|_| incremental);
+
+		if previous.is_some_and(|previous| !previous.is_older_than(max_age)) {
+			return PageOutcome::Skipped;
+		}
+
+		let page = match self.fetch_page(url, previous.is_some()).await {
+			Ok(page) => page,
+			Err(e) => {
+				self.report("scrape", &e);
+				return match e {
+					ScrapeError::NotFound { .. } => PageOutcome::Scraped {
+						url: url.to_string(),
+						metadata: None,
+						acs: None,
+					},
+					_ => PageOutcome::Skipped,
+				};
+			}
+		};
+
+		let document = Html::parse_document(&page.body);
+		let metadata = PageMetadata::new(url, actual_number, &page, &document);
+
+		if previous.is_some_and(|previous| previous.is_unchanged(&metadata)) {
+			log::debug!("Unchanged since the last run: {}", url);
+			return PageOutcome::Unchanged(metadata);
+		}
+
+		match self
+			.extract_acs(
+				&document,
+				actual_number,
+				None,
+				url,
+				&false,
+				previous.is_some(),
+			)
+			.await
+		{
+			Ok(acs) => PageOutcome::Scraped {
+				url: url.to_string(),
+				metadata: Some(metadata),
+				acs: Some(Box::new(acs)),
+			},
+			Err(e) => {
+				self.report("scrape", &e);
+				match e {
+					ScrapeError::NoAcs { .. } => PageOutcome::Scraped {
+						url: url.to_string(),
+						metadata: Some(metadata),
+						acs: None,
+					},
+					_ => PageOutcome::Skipped,
+				}
+			}
+		}
+	}
+
+	// The configured end, or the newest SCP in the name index
+	pub fn end(&self, names: &NameIndex) -> u16 {
+		self.config
+			.end
+			.or_else(|| names.max_number(&self.config.branch))
+			.unwrap_or(self.config.start)
+	}
+
+	// The numbers in the configured range, followed by the index entries of the included
+	// series in it, like SCP-1234-J. Warns about index entries the range leaves out
+	fn designations(&self, names: &NameIndex) -> Vec<SCPDesignation> {
+		let start = self.config.start;
+		let end = self.end(names);
+
+		let outside: Vec<&str> = names
+			.entries()
+			.iter()
+			.filter(|info| {
+				info.actual_number
+					.parse::<SCPDesignation>()
+					.is_ok_and(|designation| {
+						self.config.branch.is_native(&designation)
+							&& !(start..=end).contains(&designation.number)
+					})
+			})
+			.map(|info| info.actual_number.as_str())
+			.collect();
+		if !outside.is_empty() {
+			log::warn!(
+				"{} SCPs in the name index are outside the range {} to {} and are not scraped, e.g. {}",
+				outside.len(),
+				start,
+				end,
+				outside[..outside.len().min(5)].join(", ")
+			);
+		}
+
+		let mut designations: Vec<SCPDesignation> = (start..=end)
+			.filter_map(|number| self.config.branch.designation(number))
+			.collect();
+		designations.extend(
+			names
+				.entries()
+				.iter()
+				.filter_map(|info| info.actual_number.parse::<SCPDesignation>().ok())
+				.filter(|designation| {
+					self.config
+						.include
+						.iter()
+						.any(|kind| designation.suffix() == Some(kind.suffix()))
+						&& (start..=end).contains(&designation.number)
+				}),
+		);
+		designations
+	}
+
+	// Scrapes every SCP in the configured range -> Writes the ACS Database. Incremental
+	// runs merge into the existing database, others replace it
+	pub async fn scrape(&self) -> Result<Vec<Acs>> {
+		let ScraperConfig {
+			limit, incremental, ..
+		} = self.config;
+		let names = self.names()?;
+		let mut metadata: HashMap<String, PageMetadata> = self
+			.try_load::<PageMetadata>(Artifact::PageMetadata)?
+			.unwrap_or_default()
+			.into_iter()
+			.map(|page| (page.url.clone(), page))
+			.collect();
+		let mut acs_data: Vec<Acs> = if incremental {
+			self.try_load(Artifact::Database)?.unwrap_or_default()
+		} else {
+			Vec::new()
+		};
+		let (journal, done) = self.open_journal::<PageOutcome>(Stage::Scrape)?;
+
+		let designations = self.designations(&names);
+		let total = designations
+			.iter()
+			.filter(|designation| names.by_number(&designation.to_string()).is_some())
+			.count();
+
+		let progress_bar = ProgressBar::new_spinner();
+		progress_bar.set_style(ProgressStyle::default_bar()
+			.template("{msg} {spinner:.green} [{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} ({eta_precise})")
+			.expect("Failed to set progress bar style.")
+			.progress_chars("##-")
+		);
+		progress_bar.set_message("Fetching ACS data");
+		progress_bar.set_length(total as u64);
+
+		let semaphore = Arc::new(Semaphore::new(limit.into()));
+
+		let futures = designations
+			.iter()
+			.map(|designation| {
+				let actual_number = designation.to_string();
+				let scp_info = names.by_number(&actual_number);
+				let pb = progress_bar.clone();
+				let semaphore = Arc::clone(&semaphore);
+				let previous = scp_info.and_then(|info| metadata.get(&info.url));
+				let journal = &journal;
+				let done = &done;
+
+				Box::pin(async move {
+					match scp_info {
+						Some(info) => {
+							if let Some(outcome) = done.get(&info.url) {
+								pb.inc(1);
+								return Some(outcome.clone());
+							}
+
+							let _permit = semaphore
+								.acquire()
+								.await
+								.map_err(|e| {
+									error!(
+										"Failed to acquire semaphore permit for {}: {}",
+										actual_number, e
+									);
+									e
+								})
+								.ok()?;
+							let outcome = self
+								.scrape_page(&actual_number, &info.url, previous)
+								.await;
+							journal.checkpoint(&info.url, &outcome);
+							pb.inc(1);
+							Some(outcome)
+						}
+						None => {
+							log::warn!("No SCPInfo found for number: {}", actual_number);
+							None
+						}
+					}
+				})
+			})
+			.collect::<FuturesUnordered<_>>();
+
+		let results = futures.collect::<Vec<_>>().await;
+
+		for outcome in results.into_iter().flatten() {
+			match outcome {
+				PageOutcome::Skipped => {}
+				PageOutcome::Unchanged(page) => {
+					metadata.insert(page.url.clone(), page);
+				}
+				PageOutcome::Scraped {
+					url,
+					metadata: page,
+					acs,
+				} => {
+					acs_data.retain(|entry| entry.url() != Some(url.as_str()));
+					acs_data.extend(acs.map(|acs| *acs));
+					match page {
+						Some(page) => metadata.insert(url, page),
+						None => metadata.remove(&url),
+					};
+				}
+			}
+		}
+
+		progress_bar.finish_with_message("Done");
+
+		sort_json::sort(&mut acs_data, "actual_number");
+		self.write(Artifact::Database, &acs_data)?;
+
+		let mut metadata: Vec<PageMetadata> = metadata.into_values().collect();
+		metadata.sort_by(|a, b| a.url.cmp(&b.url));
+		self.write(Artifact::PageMetadata, &metadata)?;
+		journal.finish()?;
+
+		Ok(acs_data)
+	}
+
+	// Pulls the backlinks of the ACS components -> Writes the Backlinks json
+	pub async fn fetch_backlinks(&self) -> Result<Vec<BacklinksInfo>> {
+		// The names are only looked up to name the links, so the links are still worth
+		// fetching without them
+		let names = self.try_names()?.unwrap_or_else(|| {
+			log::warn!(
+				"No {} found, the backlinks are named by their numbers. Run names first to fill them in",
+				Artifact::Names.file_name()
+			);
+			Arc::new(NameIndex::default())
+		});
+		let (journal, done) = self.open_journal(Stage::Backlinks)?;
+		let backlinks = backlinks::fetch_backlinks(
+			&self.http,
+			&self.cache,
+			&names,
+			&self.config,
+			&self.errors,
+			&journal,
+			&done,
+		)
+		.await?;
+		self.write(Artifact::Backlinks, &backlinks)?;
+		journal.finish()?;
+
+		Ok(backlinks)
+	}
+
+	// Compare ACS Backlinks and add to Database if not included
+	async fn fetch_and_update_entry(
+		&self,
+		actual_number: &str,
+		name: &str,
+		url: &str,
+		fragment: bool,
+	) -> Result<Acs, ScrapeError> {
+		log::debug!("Fetching data from: {}", url);
+		let acs_data = self
+			.fetch_acs_data(actual_number, Some(name), url, &fragment)
+			.await?;
+
+		log::info!("Data fetched successfully for: {}", url);
+		match acs_data {
+			Acs::Vanilla { .. } => Ok(acs_data),
+			_ => Err(ScrapeError::Parse {
+				url: url.to_string(),
+				message: "The provided Acs data is not of variant Vanilla.".to_string(),
+			}),
+		}
+	}
+
+	pub async fn cross_compare_and_update(&self) -> Result<Vec<Acs>> {
+		let acs_bar_backlinks: Vec<BacklinksInfo> = self.load(Artifact::Backlinks)?;
+		let mut acs_database: Vec<Acs> = self.load(Artifact::Database)?;
+		let (journal, done) = self.open_journal::<Option<Acs>>(Stage::CrossCompare)?;
+
+		let semaphore = Arc::new(Semaphore::new(self.config.limit.into()));
+		let matches = Arc::new(AtomicU64::new(0));
+
+		let total_entries = acs_bar_backlinks.len() as u64;
+		let pb = ProgressBar::new(total_entries);
+		pb.set_style(
+			ProgressStyle::default_bar()
+				.template("{msg} {spinner:.green} [{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} ({eta_precise})")
+				.expect("Failed to set progress bar style.")
+				.progress_chars("##-")
+		);
+
+		let new_entries_futures: FuturesUnordered<_> = acs_bar_backlinks
+			.into_iter()
+			.filter_map(|link_item| {
+				let backlinks_number = &link_item.actual_number;
+				let backlinks_name = &link_item.name;
+
+				if acs_database.iter().any(|db_item| match db_item {
+					Acs::Vanilla {
+						actual_number,
+						name,
+						fragment,
+						..
+					} => {
+						actual_number.eq_ignore_ascii_case(backlinks_number)
+							|| name.eq_ignore_ascii_case(backlinks_name)
+							|| *fragment
+					}
+					_ => false,
+				}) {
+					None
+				} else {
+					Some(link_item)
+				}
+			})
+			.map(|link_item| {
+				let semaphore = Arc::clone(&semaphore);
+				let matches = Arc::clone(&matches);
+				let pb = pb.clone();
+				let journal = &journal;
+				let done = &done;
+
+				Box::pin(async move {
+					if let Some(entry) = done.get(&link_item.url) {
+						if entry.is_some() {
+							matches.fetch_add(1, Ordering::Relaxed);
+						}
+						return entry.clone();
+					}
+
+					let _permit = semaphore
+						.acquire()
+						.await
+						.expect("Failed to acquire semaphore");
+
+					let actual_number = &link_item.actual_number;
+					let name = &link_item.name;
+					let url = &link_item.url;
+					let fragment = link_item.fragment;
+
+					let entry = match self
+						.fetch_and_update_entry(actual_number, name, url, fragment)
+						.await
+					{
+						Ok(data) => {
+							matches.fetch_add(1, Ordering::Relaxed);
+							pb.set_message(format!(
+								"Cross comparing ACS Bar Backlinks to ACS Database - Matches: {}",
+								matches.load(Ordering::Relaxed)
+							));
+							Some(data)
+						}
+						Err(e) => {
+							self.report("cross_compare_and_update", &e);
+							None
+						}
+					};
+					journal.checkpoint(url, &entry);
+					entry
+				})
+			})
+			.collect();
+
+		let new_entries: Vec<Acs> = new_entries_futures
+			.collect::<Vec<Option<Acs>>>()
+			.await
+			.into_iter()
+			.flatten()
+			.collect::<Vec<Acs>>();
+
+		let finish_message =
+			format!("Done! - Matches: {}", matches.load(Ordering::Relaxed));
+		pb.finish_with_message(finish_message);
+
+		acs_database.extend(new_entries);
+
+		sort_json::sort(&mut acs_database, "actual_number");
+		self.write(Artifact::Database, &acs_database)?;
+		journal.finish()?;
+
+		Ok(acs_database)
+	}
+}
+
+// What scraping a single page changed
+#[derive(Serialize, Deserialize, Debug, Clone)]
+enum PageOutcome {
+	// Recent enough to not be fetched, or failed and kept as it was
+	Skipped,
+	// Fetched again but the content is the same, only the metadata is updated
+	Unchanged(PageMetadata),
+	// Fetched and scraped, replacing the entry for the URL. Without metadata the page is
+	// gone, without an entry it no longer uses ACS
+	Scraped {
+		url: String,
+		metadata: Option<PageMetadata>,
+		acs: Option<Box<Acs>>,
+	},
+}
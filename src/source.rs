@@ -0,0 +1,285 @@
+use crate::acs::{Acs, SharedAcs};
+use crate::ajax;
+use crate::cache::PageCache;
+use crate::classes::ClearanceLevel;
+use crate::http::HttpClient;
+use crate::instances::{AcsInstance, InstanceContext};
+use crate::text::clean_text;
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use scraper::{node::Node, Html, Selector};
+use std::collections::HashMap;
+
+// The component whose include arguments hold the ACS fields
+pub const ACS_BAR_COMPONENT: &str = "component:anomaly-class-bar-source";
+
+static SOURCE_SELECTOR: Lazy<Selector> =
+	Lazy::new(|| Selector::parse("div.page-source").unwrap());
+static SCRIPT_SELECTOR: Lazy<Selector> = Lazy::new(|| Selector::parse("script").unwrap());
+// Includes, and the [[tab]] and [[collapsible]] blocks around them
+static BLOCK_RGX: Lazy<Regex> = Lazy::new(|| {
+	Regex::new(r"(?is)\[\[(/?)(include|tab|collapsible)\b(.*?)\]\]").unwrap()
+});
+static INCLUDE_RGX: Lazy<Regex> =
+	Lazy::new(|| Regex::new(r"(?is)^\s+(?::[\w-]+:)?([\w:-]+)(.*)$").unwrap());
+static SHOW_RGX: Lazy<Regex> =
+	Lazy::new(|| Regex::new(r#"(?i)\bshow="([^"]*)""#).unwrap());
+
+// An [[include]] of a component in the page source, with its arguments and the tab and
+// collapsible it sits in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Include {
+	pub component: String,
+	pub args: HashMap<String, String>,
+	pub tab: Option<String>,
+	pub collapsible: Option<String>,
+}
+
+impl Include {
+	fn arg(&self, key: &str) -> &str {
+		self.args.get(key).map(String::as_str).unwrap_or("")
+	}
+}
+
+// Every include in the page source, in order. Keys are lowercased, values trimmed
+pub fn parse_includes(source: &str) -> Vec<Include> {
+	let mut includes = Vec::new();
+	let mut tabs: Vec<String> = Vec::new();
+	let mut collapsibles: Vec<String> = Vec::new();
+
+	for cap in BLOCK_RGX.captures_iter(source) {
+		let closing = !cap[1].is_empty();
+		let rest = &cap[3];
+		match (cap[2].to_lowercase().as_str(), closing) {
+			("tab", false) => tabs.push(rest.trim().to_string()),
+			("tab", true) => {
+				tabs.pop();
+			}
+			("collapsible", false) => collapsibles.push(
+				SHOW_RGX
+					.captures(rest)
+					.map(|show| show[1].trim().to_string())
+					.unwrap_or_default(),
+			),
+			("collapsible", true) => {
+				collapsibles.pop();
+			}
+			("include", false) => {
+				let Some(include) = INCLUDE_RGX.captures(rest) else {
+					continue;
+				};
+				includes.push(Include {
+					component: include[1].to_lowercase(),
+					args: include[2]
+						.split('|')
+						.filter_map(|arg| arg.split_once('='))
+						.map(|(key, value)| {
+							(key.trim().to_lowercase(), value.trim().to_string())
+						})
+						.collect(),
+					tab: tabs.last().cloned(),
+					collapsible: collapsibles.last().cloned(),
+				});
+			}
+			_ => {}
+		}
+	}
+
+	includes
+}
+
+// Every ACS Bar include with classes, in order. The first one is the primary instance
+pub fn extract_instances_from_source(source: &str) -> Vec<AcsInstance> {
+	parse_includes(source)
+		.iter()
+		.filter(|include| include.component == ACS_BAR_COMPONENT)
+		.filter_map(|include| Some((include, include_acs(include)?)))
+		.enumerate()
+		.map(|(order, (include, acs))| AcsInstance {
+			context: InstanceContext {
+				order,
+				tab: include.tab.clone(),
+				collapsible: include.collapsible.clone(),
+			},
+			primary: order == 0,
+			acs,
+		})
+		.collect()
+}
+
+// The ACS fields passed to the first ACS Bar component, None when the page does not
+// include it or passes it no classes
+pub fn extract_acs_from_source(source: &str) -> Option<Acs> {
+	extract_instances_from_source(source)
+		.into_iter()
+		.next()
+		.map(|instance| instance.acs)
+}
+
+fn include_acs(include: &Include) -> Option<Acs> {
+	let contain = clean_text(include.arg("container-class"));
+	let secondary = clean_text(include.arg("secondary-class"));
+	let disrupt = clean_text(include.arg("disruption-class"));
+	let risk = clean_text(include.arg("risk-class"));
+	if contain.is_empty() && disrupt.is_empty() && risk.is_empty() {
+		return None;
+	}
+
+	let level = ClearanceLevel::normalize(include.arg("clearance"));
+	Some(Acs::Bar {
+		shared: SharedAcs {
+			contain,
+			secondary,
+			disrupt,
+			scraper: "ACS Source".to_string(),
+		},
+		clearance: level.map(|level| level.to_string()).unwrap_or_default(),
+		clearance_text: level
+			.map(|level| level.text().to_string())
+			.unwrap_or_default(),
+		risk,
+	})
+}
+
+// The page id from the inline scripts of a rendered page
+pub fn page_id(document: &Html) -> Option<String> {
+	document
+		.select(&SCRIPT_SELECTOR)
+		.find_map(|script| ajax::parse_page_id(&script.text().collect::<String>()))
+}
+
+// The wikitext shown by ViewSourceModule, which renders line breaks as <br> and
+// indentation as non-breaking spaces
+pub fn parse_view_source(document: &Html) -> Option<String> {
+	let source = document.select(&SOURCE_SELECTOR).next()?;
+	let mut text = String::new();
+	for node in source.descendants() {
+		match node.value() {
+			Node::Text(part) => text.push_str(part),
+			Node::Element(element) if element.name() == "br" => text.push('\n'),
+			_ => {}
+		}
+	}
+
+	Some(text.replace('\u{a0}', " "))
+}
+
+// The source has to be refreshed along with a page whose HTML is refreshed, or an edited
+// page would be read from its old source
+pub async fn fetch_page_source(
+	http: &HttpClient,
+	cache: &PageCache,
+	site_url: &str,
+	page_id: &str,
+	refresh: bool,
+) -> Result<Option<String>> {
+	let document = ajax::request_module(
+		http,
+		cache,
+		site_url,
+		"viewsource/ViewSourceModule",
+		page_id,
+		&ajax::token(),
+		refresh,
+	)
+	.await?;
+
+	Ok(document.as_ref().and_then(parse_view_source))
+}
+
+#[test]
+fn test_extract_acs_from_source() {
+	let document = Html::parse_document(
+		r#"<h1>Page source</h1><div class="page-source">[[include&nbsp;:scp-wiki:component:anomaly-class-bar-source<br />
+|item-number=173<br />
+|clearance=3<br />
+|container-class=euclid<br />
+|secondary-class=none<br />
+|disruption-class=keneq<br />
+|risk-class=danger<br />
+]]<br />
+<br />
+**Special Containment Procedures:** [...]</div>"#,
+	);
+	let source = parse_view_source(&document).unwrap();
+	assert!(source.starts_with("[[include :scp-wiki:component"));
+
+	let includes = parse_includes(&source);
+	assert_eq!(includes.len(), 1);
+	assert_eq!(includes[0].arg("item-number"), "173");
+
+	match extract_acs_from_source(&source) {
+		Some(Acs::Bar {
+			shared,
+			clearance,
+			clearance_text,
+			risk,
+		}) => {
+			assert_eq!(shared.contain, "euclid");
+			assert_eq!(shared.secondary, "");
+			assert_eq!(shared.disrupt, "keneq");
+			assert_eq!(shared.scraper, "ACS Source");
+			assert_eq!(clearance, "LEVEL 3");
+			assert_eq!(clearance_text, "Confidential");
+			assert_eq!(risk, "danger");
+		}
+		other => panic!("Expected the ACS Bar arguments, got {:?}", other),
+	}
+
+	assert!(
+		extract_acs_from_source("[[include :scp-wiki:component:license-box]]").is_none()
+	);
+}
+
+#[test]
+fn test_instances_from_source() {
+	let source = r#"[[tabview]]
+[[tab Original]]
+[[include :scp-wiki:component:anomaly-class-bar-source
+|container-class=euclid
+|disruption-class=dark
+|risk-class=notice
+]]
+[[/tab]]
+[[tab Post-Incident]]
+[[include :scp-wiki:component:anomaly-class-bar-source
+|container-class=keter
+|disruption-class=keneq
+|risk-class=danger
+]]
+[[collapsible show="+ Access Addendum" hide="- Close"]]
+[[include :scp-wiki:component:anomaly-class-bar-source
+|container-class=neutralized
+|disruption-class=dark
+|risk-class=notice
+]]
+[[/collapsible]]
+[[/tab]]
+[[/tabview]]"#;
+
+	let instances = extract_instances_from_source(source);
+	let context: Vec<_> = instances
+		.iter()
+		.map(|instance| {
+			(
+				instance.context.order,
+				instance.primary,
+				instance.context.tab.as_deref(),
+				instance.context.collapsible.as_deref(),
+			)
+		})
+		.collect();
+	assert_eq!(
+		context,
+		vec![
+			(0, true, Some("Original"), None),
+			(1, false, Some("Post-Incident"), None),
+			(2, false, Some("Post-Incident"), Some("+ Access Addendum")),
+		]
+	);
+	match extract_acs_from_source(source) {
+		Some(Acs::Bar { shared, .. }) => assert_eq!(shared.contain, "euclid"),
+		other => panic!("Expected the first ACS Bar, got {:?}", other),
+	}
+}
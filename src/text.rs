@@ -0,0 +1,161 @@
+use crate::designation::SCPDesignation;
+use regex::Regex;
+use scraper::{ElementRef, Selector};
+
+pub fn format_number(number: u16) -> String {
+	SCPDesignation::new(number).to_string()
+}
+
+pub fn extract_text(element: ElementRef, selector: &Selector) -> Option<String> {
+	element
+		.select(selector)
+		.next()?
+		.text()
+		.collect::<Vec<_>>()
+		.join("")
+		.trim()
+		.to_string()
+		.into()
+}
+
+pub fn extract_class(element: ElementRef, selector: &Selector) -> Option<String> {
+	element
+		.select(selector)
+		.next()?
+		.value()
+		.attr("class")
+		.map(|s| s.to_string())
+}
+
+pub fn is_valid_containment_class(class: &str) -> bool {
+	[
+		"safe",
+		"euclid",
+		"keter",
+		"neutralized",
+		"pending",
+		"explained",
+		"esoteric",
+	]
+	.iter()
+	.any(|&valid_class| class.eq_ignore_ascii_case(valid_class))
+}
+
+pub fn clearance_level_conversion(text: &str) -> String {
+	let re = Regex::new(r"\d").unwrap();
+
+	if let Some(mat) = re.find(text) {
+		let i = mat.as_str().parse::<i32>().unwrap();
+		return format!("LEVEL {}", i);
+	}
+
+	text.to_string()
+}
+
+#[test]
+fn test_clearance_level_conversion() {
+	const TEST_STRINGS: [(&str, &str); 12] = [
+		("LEVEL 3/EE-7372", "LEVEL 3"),
+		("CLASS 5/PL-046", "LEVEL 5"),
+		("DANGER 1 - 3719", "LEVEL 1"),
+		("4/3787", "LEVEL 4"),
+		("Level 0 (For Official Use Only)", "LEVEL 0"),
+		("Level 1 (Confidential)", "LEVEL 1"),
+		("Level 2 (Restricted)", "LEVEL 2"),
+		("Level 3 (Secret)", "LEVEL 3"),
+		("Level 4 (Top Secret)", "LEVEL 4"),
+		("Level 4/2317", "LEVEL 4"),
+		("Level 5 (Thaumiel)", "LEVEL 5"),
+		("Clearance Level 6", "LEVEL 6"),
+	];
+
+	for (input, expected) in TEST_STRINGS {
+		let result = clearance_level_conversion(input);
+		assert_eq!(
+			result.as_str(),
+			expected,
+			"Expected '{}' for input '{}', but got '{}'",
+			expected,
+			input,
+			result
+		);
+	}
+}
+
+pub fn extract_string_after_colon(text: &str) -> String {
+	text.split_once(':')
+		.map(|(_, rest)| rest.split_once('\n').unwrap_or((rest, "")).0.trim_start())
+		.unwrap_or("")
+		.to_string()
+}
+
+#[test]
+fn test_extract_string_after_colon() {
+	const TEST_STRINGS: [(&str, &str); 8] = [
+		("containment class: keter\n", "keter"),
+		("disruption class: amida\n", "amida"),
+		("risk class: critical\n", "critical"),
+		("secondary class: foo bar\n", "foo bar"),
+		(
+			"containment class: {field left blank}\n",
+			"{field left blank}",
+		),
+		("no colon here\n", ""),
+		("", ""),
+		("containment class: ", ""),
+	];
+
+	for (input, expected) in TEST_STRINGS {
+		let result = extract_string_after_colon(input);
+		assert_eq!(
+			result.as_str(),
+			expected,
+			"Expected '{}' for input '{}', but got '{}'",
+			expected,
+			input,
+			result
+		);
+	}
+}
+
+pub fn clean_text(text: &str) -> String {
+	let text = text.trim().to_string();
+	if text.contains("{$") || text.eq_ignore_ascii_case("none") {
+		return String::new();
+	}
+	if text.contains(':') {
+		return extract_string_after_colon(&text);
+	}
+	if !(text.contains("n/a") || text.contains("N/A")) && text.contains('/') {
+		text.split_once('/')
+			.map(|(_, rest)| rest)
+			.unwrap_or("")
+			.to_string()
+	} else {
+		text
+	}
+}
+
+#[test]
+fn test_clean_text() {
+	const TEST_STRINGS: [(&str, &str); 6] = [
+		("Containment Class: Keter", "Keter"),
+		("Secondary Class: {FIELD LEFT BLANK}", "{FIELD LEFT BLANK}"),
+		("{$secondary-class}", ""),
+		("2/Vlam", "Vlam"),
+		("None", ""),
+		("N/A", "N/A"),
+	];
+
+	for (input, expected) in TEST_STRINGS {
+		let result = clean_text(input);
+		assert_eq!(
+			result.as_str(),
+			expected,
+			"Expected '{}' for input '{}', but got '{}'",
+			expected,
+			input,
+			result
+		);
+	}
+}
@@ -0,0 +1,131 @@
+use crate::acs::{Acs, SCPInfo};
+use std::collections::HashSet;
+
+// A problem found with a single database entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+	pub entry: String,
+	pub problem: String,
+}
+
+// Checks the database for entries a scrape should not have produced: unfinished or
+// duplicate entries, missing names, numbers missing from the names file and classes
+// outside the canonical vocabulary
+pub fn validate_database(entries: &[Acs], names: &[SCPInfo]) -> Vec<ValidationIssue> {
+	let known_numbers: HashSet<&str> = names
+		.iter()
+		.map(|info| info.actual_number.as_str())
+		.collect();
+	let mut seen_urls: HashSet<&str> = HashSet::new();
+	let mut issues = Vec::new();
+
+	for acs in entries {
+		let Acs::Vanilla {
+			shared,
+			name,
+			actual_number,
+			clearance,
+			risk,
+			url,
+			fragment,
+			..
+		} = acs
+		else {
+			issues.push(ValidationIssue {
+				entry: format!("{:?}", acs),
+				problem: "Entry is missing its page details".to_string(),
+			});
+			continue;
+		};
+
+		let mut problem = |problem: String| {
+			issues.push(ValidationIssue {
+				entry: format!("{} ({})", actual_number, url),
+				problem,
+			})
+		};
+		let classes = acs.normalized_classes();
+
+		if !seen_urls.insert(url) {
+			problem("Duplicate entry for the URL".to_string());
+		}
+		if name.trim().is_empty() {
+			problem("Missing name".to_string());
+		}
+		if !fragment
+			&& actual_number.starts_with("SCP-")
+			&& !known_numbers.is_empty()
+			&& !known_numbers.contains(actual_number.as_str())
+		{
+			problem("Number is not in scp_names.json".to_string());
+		}
+		if classes.contain.is_none() {
+			problem(format!("No containment class in '{}'", shared.contain));
+		}
+		if classes.disrupt.is_none() && !shared.disrupt.trim().is_empty() {
+			problem(format!(
+				"Unrecognised disruption class '{}'",
+				shared.disrupt
+			));
+		}
+		if classes.risk.is_none() && !risk.trim().is_empty() {
+			problem(format!("Unrecognised risk class '{}'", risk));
+		}
+		if classes.clearance.is_none() && !clearance.trim().is_empty() {
+			problem(format!("Unrecognised clearance level '{}'", clearance));
+		}
+	}
+
+	issues
+}
+
+#[test]
+fn test_validate_database() {
+	use crate::acs::SharedAcs;
+
+	let entry = |number: &str, contain: &str, disrupt: &str| Acs::Vanilla {
+		shared: SharedAcs {
+			contain: contain.to_string(),
+			secondary: String::new(),
+			disrupt: disrupt.to_string(),
+			scraper: "ACS Bar".to_string(),
+		},
+		name: "Name".to_string(),
+		actual_number: number.to_string(),
+		display_number: String::new(),
+		clearance: "LEVEL 2".to_string(),
+		clearance_text: String::new(),
+		risk: String::new(),
+		url: format!("https://scp-wiki.wikidot.com/{}", number.to_lowercase()),
+		fragment: false,
+		classes: Default::default(),
+		instances: Vec::new(),
+		regions: Default::default(),
+	};
+	let names = vec![SCPInfo::new(
+		"SCP-002",
+		"The \"Living\" Room",
+		"https://scp-wiki.wikidot.com/scp-002",
+	)];
+
+	assert!(validate_database(&[entry("SCP-002", "Keter", "Amida")], &names).is_empty());
+
+	let issues = validate_database(
+		&[
+			entry("SCP-002", "{FIELD LEFT BLANK}", "Omega"),
+			entry("SCP-002", "Keter", ""),
+			entry("SCP-9999", "Safe", ""),
+		],
+		&names,
+	);
+	let problems: Vec<&str> = issues.iter().map(|i| i.problem.as_str()).collect();
+	assert_eq!(
+		problems,
+		vec![
+			"No containment class in '{FIELD LEFT BLANK}'",
+			"Unrecognised disruption class 'Omega'",
+			"Duplicate entry for the URL",
+			"Number is not in scp_names.json",
+		]
+	);
+}
@@ -0,0 +1,145 @@
+// Fetches the backlinks of a component from a local stand-in for the wiki, where one of
+// the pages wrapping the component and a fragment including it have been deleted
+
+mod common;
+
+use acs_database_rs::{
+	AcsScraper, Artifact, BacklinkComponent, Branch, CacheMode, ErrorKind, JsonFileSink,
+	OutputSink, PageCache, ScraperConfig,
+};
+use std::sync::Arc;
+
+// The BacklinksModule response listing the given links
+fn backlinks_response(links: &[&str]) -> String {
+	let items: String = links
+		.iter()
+		.map(|link| format!(r#"<li><a href="{0}">{0}</a></li>"#, link))
+		.collect();
+	let body = format!(
+		r#"<div class="backlinks-module-box"><ul>{}</ul></div>"#,
+		items
+	);
+	serde_json::json!({ "status": "ok", "body": body }).to_string()
+}
+
+#[tokio::test]
+async fn failing_wrapper_is_skipped() {
+	// The component is included by SCP-173, a fragment and two wrappers. The fragment
+	// and one of the wrappers are deleted
+	let addr = common::stand_in_wiki(|request| {
+		if request.starts_with("POST ") && request.contains("page_id=1&") {
+			(
+				"200 OK",
+				backlinks_response(&[
+					"/scp-173",
+					"/fragment:scp-173-1",
+					"/component:gone",
+					"/theme:kept",
+				]),
+			)
+		} else if request.starts_with("POST ") && request.contains("page_id=2&") {
+			("200 OK", backlinks_response(&["/scp-002"]))
+		} else if request.starts_with("GET /theme:kept ") {
+			(
+				"200 OK",
+				"<script>WIKIREQUEST.info.pageId = 2;</script>".to_string(),
+			)
+		} else {
+			("404 Not Found", String::new())
+		}
+	})
+	.await;
+	let site_url = format!("http://{}", addr);
+
+	let dir = common::TempDir::new("backlinks");
+	let sink = JsonFileSink::new(&dir);
+	sink.prepare().unwrap();
+	let names = vec![
+		common::scp_info(&site_url, "SCP-002"),
+		common::scp_info(&site_url, "SCP-173"),
+	];
+	sink.write(Artifact::Names, &serde_json::to_value(names).unwrap())
+		.unwrap();
+
+	let sinks: Vec<Arc<dyn OutputSink>> = vec![Arc::new(sink)];
+	let scraper = AcsScraper::new(ScraperConfig {
+		backlink_components: vec![BacklinkComponent {
+			page_id: Some("1".to_string()),
+			..BacklinkComponent::new("component:bar", "Bar")
+		}],
+		branch: Branch {
+			site_url: site_url.clone(),
+			..Branch::default()
+		},
+		..ScraperConfig::default()
+	})
+	.with_http(common::http_client())
+	.with_cache(PageCache::new("cache", CacheMode::Disabled))
+	.with_journal_dir(&dir)
+	.with_sinks(sinks);
+
+	let backlinks = scraper.fetch_backlinks().await.unwrap();
+	let urls: Vec<&str> = backlinks.iter().map(|link| link.url.as_str()).collect();
+	assert_eq!(
+		urls,
+		vec![
+			format!("{}/scp-173", site_url),
+			format!("{}/fragment:scp-173-1", site_url),
+			format!("{}/scp-002", site_url),
+		]
+	);
+	assert_eq!(backlinks[0].actual_number, "SCP-173");
+	assert_eq!(backlinks[2].actual_number, "SCP-002");
+
+	let mut not_found = scraper.errors().grouped()[&ErrorKind::NotFound].clone();
+	not_found.sort();
+	assert_eq!(
+		not_found,
+		vec![
+			format!("Page not found: {}/component:gone", site_url),
+			format!(
+				"Page not found: {}/fragment:scp-173-1/norender/true",
+				site_url
+			),
+		]
+	);
+}
+
+#[tokio::test]
+async fn missing_names_fall_back_to_numbers() {
+	let addr = common::stand_in_wiki(|request| {
+		if request.starts_with("POST ") {
+			("200 OK", backlinks_response(&["/scp-173"]))
+		} else {
+			("404 Not Found", String::new())
+		}
+	})
+	.await;
+	let site_url = format!("http://{}", addr);
+
+	let dir = common::TempDir::new("backlinks-names");
+	let sink = JsonFileSink::new(&dir);
+	sink.prepare().unwrap();
+
+	let sinks: Vec<Arc<dyn OutputSink>> = vec![Arc::new(sink)];
+	let scraper = AcsScraper::new(ScraperConfig {
+		backlink_components: vec![BacklinkComponent {
+			page_id: Some("1".to_string()),
+			..BacklinkComponent::new("component:bar", "Bar")
+		}],
+		branch: Branch {
+			site_url: site_url.clone(),
+			..Branch::default()
+		},
+		..ScraperConfig::default()
+	})
+	.with_http(common::http_client())
+	.with_cache(PageCache::new("cache", CacheMode::Disabled))
+	.with_journal_dir(&dir)
+	.with_sinks(sinks);
+
+	let backlinks = scraper.fetch_backlinks().await.unwrap();
+	assert_eq!(backlinks.len(), 1);
+	assert_eq!(backlinks[0].actual_number, "SCP-173");
+	assert_eq!(backlinks[0].name, "SCP-173");
+}
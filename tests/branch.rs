@@ -0,0 +1,79 @@
+// Scrapes a page of the French branch from a local stand-in for its wiki, to check that
+// the localised labels are found and the classes mapped to the English ones
+
+mod common;
+
+use acs_database_rs::{
+	Acs, AcsScraper, Branch, CacheMode, ContainmentClass, Keywords, PageCache, RiskClass,
+	ScraperConfig,
+};
+use std::collections::BTreeMap;
+
+const PAGE: &str = r#"<html><body><div id="page-content">
+<p><strong>Objet n° :</strong> SCP-001-FR</p>
+<p><strong>Classe de confinement :</strong> Euclide</p>
+<p><strong>Classe de risque :</strong> Modéré</p>
+</div></body></html>"#;
+
+fn french() -> Branch {
+	Branch {
+		name: "fr".to_string(),
+		number_format: "SCP-{}-FR".to_string(),
+		keywords: Keywords {
+			containment: vec!["classe de confinement :".to_string()],
+			risk: vec!["classe de risque :".to_string()],
+			..Keywords::default()
+		},
+		classes: BTreeMap::from([
+			("euclide".to_string(), "euclid".to_string()),
+			("modéré".to_string(), "caution".to_string()),
+		]),
+		..Branch::default()
+	}
+}
+
+#[tokio::test]
+async fn localised_classes_are_translated() {
+	let addr = common::stand_in_wiki(|request| {
+		if request.starts_with("GET /scp-001-fr ") {
+			("200 OK", PAGE.to_string())
+		} else {
+			("404 Not Found", String::new())
+		}
+	})
+	.await;
+
+	let branch = Branch {
+		site_url: format!("http://{}", addr),
+		..french()
+	};
+	let acs = AcsScraper::new(ScraperConfig {
+		branch,
+		..ScraperConfig::default()
+	})
+	.with_http(common::http_client())
+	.with_cache(PageCache::new("cache", CacheMode::Disabled))
+	.fetch_acs_data(
+		"SCP-001-FR",
+		Some("Le Gardien"),
+		&format!("http://{}/scp-001-fr", addr),
+		&false,
+	)
+	.await
+	.unwrap();
+
+	match acs {
+		Acs::Vanilla {
+			shared,
+			risk,
+			classes,
+			..
+		} => {
+			assert_eq!(shared.contain, "euclide");
+			assert_eq!(risk, "modéré");
+			assert_eq!(classes.contain, Some(ContainmentClass::Euclid));
+			assert_eq!(classes.risk, Some(RiskClass::Caution));
+		}
+		other => panic!("Expected a finished entry, got {:?}", other),
+	}
+}
@@ -0,0 +1,104 @@
+// A local stand-in for the wiki, shared by the integration tests that run the scraper
+// against it
+
+use acs_database_rs::{HttpClient, HttpConfig, RetryPolicy, SCPInfo};
+use std::{
+	fs,
+	net::SocketAddr,
+	ops::Deref,
+	path::{Path, PathBuf},
+	sync::Arc,
+};
+use tokio::{
+	io::{AsyncReadExt, AsyncWriteExt},
+	net::TcpListener,
+};
+
+// Answers every request with the status line and body `route` returns for it. The route
+// gets the whole request, e.g. "GET /scp-173 HTTP/1.1\r\n..." or a POST with its form
+pub async fn stand_in_wiki<F>(route: F) -> SocketAddr
+where
+	F: Fn(&str) -> (&'static str, String) + Send + Sync + 'static,
+{
+	let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+	let addr = listener.local_addr().unwrap();
+	let route = Arc::new(route);
+
+	tokio::spawn(async move {
+		loop {
+			let (mut socket, _) = listener.accept().await.unwrap();
+			let route = Arc::clone(&route);
+			tokio::spawn(async move {
+				let mut buffer = vec![0; 16 * 1024];
+				let read = socket.read(&mut buffer).await.unwrap();
+				let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+
+				let (status, body) = route(&request);
+
+				let response = format!(
+					"HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+					status,
+					body.len(),
+					body
+				);
+				socket.write_all(response.as_bytes()).await.unwrap();
+			});
+		}
+	});
+
+	addr
+}
+
+// A directory for a test's output, journals and cache, removed when the guard is
+// dropped so a failing test does not leave it behind
+pub struct TempDir(PathBuf);
+
+#[allow(dead_code)]
+impl TempDir {
+	// `name` keeps the directories of the tests in one process apart
+	pub fn new(name: &str) -> Self {
+		TempDir(std::env::temp_dir().join(format!(
+			"acs-{}-test-{}",
+			name,
+			std::process::id()
+		)))
+	}
+}
+
+impl Deref for TempDir {
+	type Target = Path;
+
+	fn deref(&self) -> &Path {
+		&self.0
+	}
+}
+
+impl AsRef<Path> for TempDir {
+	fn as_ref(&self) -> &Path {
+		&self.0
+	}
+}
+
+impl Drop for TempDir {
+	fn drop(&mut self) {
+		let _ = fs::remove_dir_all(&self.0);
+	}
+}
+
+// An SCP on the stand-in wiki, named after its number
+#[allow(dead_code)]
+pub fn scp_info(site_url: &str, number: &str) -> SCPInfo {
+	let url = format!("{}/{}", site_url, number.to_lowercase());
+	SCPInfo::new(number, &format!("Name of {}", number), &url)
+}
+
+// No rate limit and no retries, so a failing route fails at once. Not every test needs it
+#[allow(dead_code)]
+pub fn http_client() -> HttpClient {
+	HttpClient::new(HttpConfig {
+		requests_per_second: 0.0,
+		retry: RetryPolicy::none(),
+		..HttpConfig::default()
+	})
+	.unwrap()
+}
@@ -0,0 +1,144 @@
+// Golden-file regression tests.
+//
+// Every input under `tests/fixtures/<kind>/` is parsed and compared against the
+// `<name>.expected.json` file next to it. Run with `UPDATE_GOLDEN=1` to (re)write the
+// expected files after an intentional change, then review the diff.
+
+use acs_database_rs::{acs::finish_acs, backlinks, names, Branch, ExtractorRegistry};
+use anyhow::Result;
+use scraper::{Html, Selector};
+use serde_json::Value;
+use std::{
+	fs,
+	path::{Path, PathBuf},
+};
+
+fn fixtures_dir(kind: &str) -> PathBuf {
+	Path::new(env!("CARGO_MANIFEST_DIR"))
+		.join("tests")
+		.join("fixtures")
+		.join(kind)
+}
+
+fn expected_path(input: &Path) -> PathBuf {
+	let stem = input.file_stem().unwrap().to_string_lossy();
+	input.with_file_name(format!("{}.expected.json", stem))
+}
+
+fn run_golden(kind: &str, parse: impl Fn(&str) -> Result<Value>) {
+	let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+	let mut inputs: Vec<PathBuf> = fs::read_dir(fixtures_dir(kind))
+		.unwrap_or_else(|e| panic!("Missing fixtures for {}: {}", kind, e))
+		.map(|entry| entry.unwrap().path())
+		.filter(|path| !path.to_string_lossy().ends_with(".expected.json"))
+		.collect();
+	inputs.sort();
+	assert!(!inputs.is_empty(), "No {} fixtures found", kind);
+
+	let mut failures = Vec::new();
+
+	for input in &inputs {
+		let contents = fs::read_to_string(input).unwrap();
+		let actual = parse(&contents)
+			.unwrap_or_else(|e| panic!("Failed to parse {}: {:?}", input.display(), e));
+		let expected_path = expected_path(input);
+
+		if update {
+			let mut json = serde_json::to_string_pretty(&actual).unwrap();
+			json.push('\n');
+			fs::write(&expected_path, json).unwrap();
+			continue;
+		}
+
+		let expected: Value = match fs::read_to_string(&expected_path) {
+			Ok(expected) => serde_json::from_str(&expected).unwrap(),
+			Err(_) => {
+				failures.push(format!(
+					"{}: missing {} (run with UPDATE_GOLDEN=1)",
+					input.display(),
+					expected_path.display()
+				));
+				continue;
+			}
+		};
+
+		if actual != expected {
+			failures.push(format!(
+				"{}:\nexpected: {}\n  actual: {}",
+				input.display(),
+				serde_json::to_string_pretty(&expected).unwrap(),
+				serde_json::to_string_pretty(&actual).unwrap()
+			));
+		}
+	}
+
+	assert!(failures.is_empty(), "\n{}", failures.join("\n\n"));
+}
+
+// Runs the whole pipeline a scraped page goes through, up to the finished entry, with
+// the page's identity taken from its title
+#[test]
+fn golden_extractors() {
+	let registry = ExtractorRegistry::default();
+	let branch = Branch::default();
+	run_golden("extractors", |html| {
+		let document = Html::parse_document(html);
+		let title = document
+			.select(&Selector::parse("#page-title").unwrap())
+			.next()
+			.map(|title| title.text().collect::<String>().trim().to_string())
+			.unwrap_or_default();
+		let slug = title.to_lowercase();
+
+		let acs = finish_acs(
+			&branch,
+			registry.extract_instances(&document),
+			"",
+			&title,
+			"",
+			&branch.url(&format!("/{}", slug)),
+			&slug.starts_with("fragment:"),
+		)?;
+		Ok(serde_json::to_value(acs)?)
+	});
+}
+
+#[test]
+fn golden_series_pages() {
+	run_golden("series", |html| {
+		let document = Html::parse_document(html);
+		Ok(serde_json::to_value(names::parse_series_page(
+			&document,
+			&Branch::default(),
+		))?)
+	});
+}
+
+#[test]
+fn golden_backlinks_responses() {
+	run_golden("backlinks", |body| {
+		Ok(serde_json::to_value(backlinks::parse_backlinks_response(
+			body,
+			&Branch::default().site_url,
+		)?)?)
+	});
+}
+
+#[test]
+fn golden_fragment_parents() {
+	run_golden("fragments", |html| {
+		let document = Html::parse_document(html);
+		Ok(serde_json::to_value(backlinks::parse_fragment_parent(
+			&document,
+		))?)
+	});
+}
+
+#[test]
+fn golden_instances() {
+	let registry = ExtractorRegistry::default();
+	run_golden("instances", |html| {
+		let document = Html::parse_document(html);
+		Ok(serde_json::to_value(registry.extract_instances(&document))?)
+	});
+}
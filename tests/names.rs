@@ -0,0 +1,87 @@
+// Runs the names stage against a local stand-in for the wiki with two series hubs, to
+// check that the hubs are discovered and the end of the range follows the name index
+
+mod common;
+
+use acs_database_rs::{
+	AcsScraper, Artifact, Branch, CacheMode, JsonFileSink, OutputSink, PageCache,
+	ScraperConfig,
+};
+use std::{net::SocketAddr, path::Path, sync::Arc};
+
+fn series_page(numbers: &[u16]) -> String {
+	let items: String = numbers
+		.iter()
+		.map(|number| {
+			format!(
+				r#"<li><a href="/scp-{0:03}">SCP-{0:03}</a> - Entry {0}</li>"#,
+				number
+			)
+		})
+		.collect();
+	format!(
+		r#"<div id="page-content"><h1 id="toc1"><span>Series</span></h1><ul>{}</ul></div>"#,
+		items
+	)
+}
+
+// Serves /scp-series and /scp-series-2, everything else is a 404
+async fn stand_in_wiki() -> SocketAddr {
+	common::stand_in_wiki(|request| {
+		if request.starts_with("GET /scp-series ") {
+			("200 OK", series_page(&[2, 173]))
+		} else if request.starts_with("GET /scp-series-2 ") {
+			("200 OK", series_page(&[1000, 1999]))
+		} else {
+			("404 Not Found", String::new())
+		}
+	})
+	.await
+}
+
+fn scraper(addr: SocketAddr, cache: PageCache, dir: &Path) -> AcsScraper {
+	let sink = JsonFileSink::new(dir);
+	sink.prepare().unwrap();
+	let sinks: Vec<Arc<dyn OutputSink>> = vec![Arc::new(sink)];
+
+	AcsScraper::new(ScraperConfig {
+		branch: Branch {
+			site_url: format!("http://{}", addr),
+			..Branch::default()
+		},
+		..ScraperConfig::default()
+	})
+	.with_http(common::http_client())
+	.with_cache(cache)
+	.with_sinks(sinks)
+}
+
+#[tokio::test]
+async fn series_hubs_are_discovered() {
+	let addr = stand_in_wiki().await;
+	let dir = common::TempDir::new("names");
+	let scraper = scraper(addr, PageCache::new("cache", CacheMode::Disabled), &dir);
+
+	let names = scraper.init_scp_names_json().await.unwrap();
+	let numbers: Vec<&str> = names
+		.iter()
+		.map(|info| info.actual_number.as_str())
+		.collect();
+	assert_eq!(numbers, vec!["SCP-002", "SCP-173", "SCP-1000", "SCP-1999"]);
+	assert_eq!(scraper.end(&scraper.names().unwrap()), 1999);
+}
+
+// A hub missing from the cache is not the end of the series
+#[tokio::test]
+async fn cold_cache_is_an_error() {
+	let addr = stand_in_wiki().await;
+	let dir = common::TempDir::new("names-cold");
+	let cache = PageCache::new(dir.join("cache"), CacheMode::CacheOnly);
+	let scraper = scraper(addr, cache, &dir);
+
+	assert!(scraper.init_scp_names_json().await.is_err());
+	assert!(JsonFileSink::new(&dir)
+		.read(Artifact::Names)
+		.unwrap()
+		.is_none());
+}
@@ -0,0 +1,92 @@
+// Requests a page from a local stand-in for the wiki that is unavailable at first, to
+// check what the attempt log records for the run summary
+
+mod common;
+
+use acs_database_rs::{HttpClient, HttpConfig, RetryPolicy};
+use std::{
+	sync::{
+		atomic::{AtomicU32, Ordering},
+		Arc,
+	},
+	time::Duration,
+};
+
+#[tokio::test]
+async fn retries_are_logged_with_their_reasons() {
+	let requests = Arc::new(AtomicU32::new(0));
+	let counted = Arc::clone(&requests);
+	let addr =
+		common::stand_in_wiki(move |_| match counted.fetch_add(1, Ordering::SeqCst) {
+			0 => ("503 Service Unavailable", String::new()),
+			1 => ("502 Bad Gateway", String::new()),
+			_ => ("200 OK", "<p>SCP-173</p>".to_string()),
+		})
+		.await;
+
+	let http = HttpClient::new(HttpConfig {
+		requests_per_second: 0.0,
+		retry: RetryPolicy {
+			initial_backoff: Duration::from_millis(10),
+			jitter: 0.0,
+			..RetryPolicy::default()
+		},
+		..HttpConfig::default()
+	})
+	.unwrap();
+	let url = format!("http://{}/scp-173", addr);
+	http.get(&url).await.unwrap();
+
+	let retried = http.attempts().retried();
+	assert_eq!(retried.len(), 1);
+	let (retried_url, attempts) = &retried[0];
+	assert_eq!(retried_url, &url);
+	assert_eq!(attempts.attempts, 3);
+	assert_eq!(
+		attempts.retried_for,
+		vec!["503 Service Unavailable", "502 Bad Gateway"]
+	);
+	assert_eq!(attempts.outcome, "200 OK");
+}
+
+// The first response promises more body than it sends before the connection closes
+#[tokio::test]
+async fn cut_off_bodies_are_retried() {
+	use tokio::{
+		io::{AsyncReadExt, AsyncWriteExt},
+		net::TcpListener,
+	};
+
+	let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+	let addr = listener.local_addr().unwrap();
+	tokio::spawn(async move {
+		for attempt in 0.. {
+			let (mut socket, _) = listener.accept().await.unwrap();
+			let mut buffer = vec![0; 16 * 1024];
+			let _request = socket.read(&mut buffer).await.unwrap();
+			let response = match attempt {
+				0 => "HTTP/1.1 200 OK\r\nContent-Length: 100\r\nConnection: close\r\n\r\n<p>SCP",
+				_ => "HTTP/1.1 200 OK\r\nContent-Length: 14\r\nConnection: close\r\n\r\n<p>SCP-173</p>",
+			};
+			socket.write_all(response.as_bytes()).await.unwrap();
+		}
+	});
+
+	let http = HttpClient::new(HttpConfig {
+		requests_per_second: 0.0,
+		retry: RetryPolicy {
+			initial_backoff: Duration::from_millis(10),
+			jitter: 0.0,
+			..RetryPolicy::default()
+		},
+		..HttpConfig::default()
+	})
+	.unwrap();
+	let url = format!("http://{}/scp-173", addr);
+	let response = http.get(&url).await.unwrap();
+	assert_eq!(response.body, "<p>SCP-173</p>");
+
+	let retried = http.attempts().retried();
+	assert_eq!(retried.len(), 1);
+	assert_eq!(retried[0].1.retried_for, vec!["body failed"]);
+}
@@ -0,0 +1,211 @@
+// Runs the scraper against a local stand-in for the wiki, serving a rendered page and
+// the ViewSourceModule response for it
+
+mod common;
+
+use acs_database_rs::{
+	Acs, AcsScraper, Artifact, Branch, CacheMode, JsonFileSink, OutputSink, PageCache,
+	ScraperConfig,
+};
+use std::{
+	fs,
+	net::SocketAddr,
+	path::Path,
+	sync::{
+		atomic::{AtomicU32, Ordering},
+		Arc, Mutex,
+	},
+	time::Duration,
+};
+
+const PAGE_SOURCE: &str = "[[include&nbsp;:scp-wiki:component:anomaly-class-bar-source<br />|item-number=173<br />|clearance=2<br />|container-class=euclid<br />|disruption-class=dark<br />|risk-class=notice<br />]]";
+
+// The rendered page of the ACS Bar fixture, with the inline script carrying its page id
+fn rendered_page() -> String {
+	let html = fs::read_to_string(
+		Path::new(env!("CARGO_MANIFEST_DIR"))
+			.join("tests/fixtures/extractors/acs_bar.html"),
+	)
+	.unwrap();
+	html.replacen(
+		"<head>",
+		"<head><script>WIKIREQUEST.info.pageId = 1234;</script>",
+		1,
+	)
+}
+
+// Answers GET /scp-173 with the rendered page and POSTs to the module connector with the
+// page source, or with an error when `source_available` is false
+async fn stand_in_wiki(source_available: bool) -> SocketAddr {
+	common::stand_in_wiki(move |request| {
+		if request.starts_with("GET /scp-173 ") {
+			("200 OK", rendered_page())
+		} else if request.starts_with("POST /ajax-module-connector.php ")
+			&& source_available
+		{
+			("200 OK", source_response(PAGE_SOURCE))
+		} else if request.starts_with("POST ") {
+			("500 Internal Server Error", String::new())
+		} else {
+			("404 Not Found", String::new())
+		}
+	})
+	.await
+}
+
+// The ViewSourceModule response for a page source
+fn source_response(source: &str) -> String {
+	let body = format!(
+		r#"<h1>Page source</h1><div class="page-source">{}</div>"#,
+		source
+	);
+	serde_json::json!({ "status": "ok", "body": body }).to_string()
+}
+
+fn scraper(addr: SocketAddr) -> AcsScraper {
+	AcsScraper::new(ScraperConfig {
+		branch: Branch {
+			site_url: format!("http://{}", addr),
+			..Branch::default()
+		},
+		..ScraperConfig::default()
+	})
+	.with_http(common::http_client())
+	.with_cache(PageCache::new("cache", CacheMode::Disabled))
+}
+
+async fn scrape(addr: SocketAddr) -> Acs {
+	scraper(addr)
+		.fetch_acs_data(
+			"SCP-173",
+			Some("The Sculpture"),
+			&format!("http://{}/scp-173", addr),
+			&false,
+		)
+		.await
+		.unwrap()
+}
+
+#[tokio::test]
+async fn source_is_preferred_over_html() {
+	let addr = stand_in_wiki(true).await;
+
+	match scrape(addr).await {
+		Acs::Vanilla {
+			shared,
+			clearance,
+			risk,
+			..
+		} => {
+			assert_eq!(shared.scraper, "ACS Source");
+			assert_eq!(shared.contain, "euclid");
+			assert_eq!(shared.disrupt, "dark");
+			assert_eq!(clearance, "LEVEL 2");
+			assert_eq!(risk, "notice");
+		}
+		other => panic!("Expected a finished entry, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn html_is_the_fallback() {
+	let addr = stand_in_wiki(false).await;
+
+	match scrape(addr).await {
+		Acs::Vanilla { shared, .. } => {
+			assert_eq!(shared.scraper, "ACS Bar");
+			assert_eq!(shared.contain, "keter");
+		}
+		other => panic!("Expected a finished entry, got {:?}", other),
+	}
+}
+
+// An incremental run that fetches an edited page again has to fetch its source again
+// too, the cached one still has the old classes
+#[tokio::test]
+async fn edited_source_is_fetched_again() {
+	let revision = Arc::new(AtomicU32::new(1));
+	let served = Arc::clone(&revision);
+	let addr = common::stand_in_wiki(move |request| {
+		let revision = served.load(Ordering::SeqCst);
+		if request.starts_with("GET /scp-173 ") {
+			let page_info = format!("page revision: {},", revision);
+			let page = rendered_page().replacen("page revision: 7,", &page_info, 1);
+			("200 OK", page)
+		} else if request.starts_with("POST /ajax-module-connector.php ") {
+			let class = if revision == 1 { "euclid" } else { "keter" };
+			let source = PAGE_SOURCE.replace(
+				"container-class=euclid",
+				&format!("container-class={}", class),
+			);
+			("200 OK", source_response(&source))
+		} else {
+			("404 Not Found", String::new())
+		}
+	})
+	.await;
+
+	let dir = common::TempDir::new("source");
+	let sink = JsonFileSink::new(&dir);
+	sink.prepare().unwrap();
+	let names = vec![common::scp_info(&format!("http://{}", addr), "SCP-173")];
+	sink.write(Artifact::Names, &serde_json::to_value(names).unwrap())
+		.unwrap();
+
+	let run = || async {
+		let sinks: Vec<Arc<dyn OutputSink>> = vec![Arc::new(JsonFileSink::new(&dir))];
+		let database = AcsScraper::new(ScraperConfig {
+			start: 173,
+			end: Some(173),
+			incremental: true,
+			max_age: Duration::ZERO,
+			branch: Branch {
+				site_url: format!("http://{}", addr),
+				..Branch::default()
+			},
+			..ScraperConfig::default()
+		})
+		.with_http(common::http_client())
+		.with_cache(PageCache::new(dir.join("cache"), CacheMode::ReadWrite))
+		.with_journal_dir(&dir)
+		.with_sinks(sinks)
+		.scrape()
+		.await
+		.unwrap();
+
+		match &database[..] {
+			[Acs::Vanilla { shared, .. }] => shared.contain.clone(),
+			other => panic!("Expected one finished entry, got {:?}", other),
+		}
+	};
+
+	assert_eq!(run().await, "euclid");
+	revision.store(2, Ordering::SeqCst);
+	assert_eq!(run().await, "keter");
+}
+
+#[tokio::test]
+async fn referer_is_the_branch_site() {
+	let referers = Arc::new(Mutex::new(Vec::new()));
+	let seen = Arc::clone(&referers);
+	let addr = common::stand_in_wiki(move |request| {
+		if request.starts_with("GET /scp-173 ") {
+			let referer = request.lines().find_map(|line| {
+				line.to_lowercase()
+					.strip_prefix("referer: ")
+					.map(str::to_string)
+			});
+			seen.lock().unwrap().push(referer);
+			("200 OK", rendered_page())
+		} else {
+			("404 Not Found", String::new())
+		}
+	})
+	.await;
+
+	scrape(addr).await;
+	assert_eq!(
+		*referers.lock().unwrap(),
+		vec![Some(format!("http://{}/", addr))]
+	);
+}